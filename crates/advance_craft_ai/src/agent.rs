@@ -0,0 +1,122 @@
+use advance_craft_engine::{
+    Command, CommandResult, ScenarioState, TeamID,
+    rules::{GameOutcome, UndoPolicy},
+};
+
+/// A computer player.
+pub trait Agent: Send + Sync {
+    /// The commands for a whole turn, given the team's view of the game. The last command
+    /// should be `EndTurn`.
+    fn choose_commands(&mut self, view: &ScenarioState, team: TeamID) -> Vec<Command>;
+}
+
+/// The commands for the active team's turn, chosen from what the team can see. Always ends
+/// with `EndTurn`, even if the agent forgot it.
+pub fn plan_turn(agent: &mut dyn Agent, scenario_state: &ScenarioState) -> Vec<Command> {
+    let team = scenario_state.active_team;
+    let mut view = scenario_state.view_for_team(team);
+    // Searching never needs to take anything back.
+    view.rules.undo = UndoPolicy::Never;
+
+    let mut commands = agent.choose_commands(&view, team);
+    if !matches!(commands.last(), Some(Command::EndTurn)) {
+        commands.push(Command::EndTurn);
+    }
+    commands
+}
+
+/// Plays the active team's turn, returning each command with its result. The agent only sees
+/// what the team can see, so some of its commands may fail once they meet the real state, eg.
+/// when walking into a hidden unit.
+pub fn play_turn(
+    agent: &mut dyn Agent,
+    scenario_state: &mut ScenarioState,
+) -> Vec<(Command, CommandResult)> {
+    let team = scenario_state.active_team;
+
+    let mut results = vec![];
+    for command in plan_turn(agent, scenario_state) {
+        if scenario_state.active_team != team {
+            break;
+        }
+        let result = scenario_state.execute(command.clone());
+        results.push((command, result));
+    }
+    results
+}
+
+/// Plays a game between agents, one per team in the order of `scenario_state.teams`, until
+/// somebody wins or `max_turns` turns have been played.
+pub fn play_game(
+    agents: &mut [Box<dyn Agent>],
+    scenario_state: &mut ScenarioState,
+    max_turns: u32,
+) -> GameOutcome {
+    for _ in 0..max_turns {
+        let outcome = scenario_state.outcome();
+        if outcome != GameOutcome::Ongoing {
+            return outcome;
+        }
+
+        let Some(index) = scenario_state
+            .teams
+            .iter()
+            .position(|team| *team == scenario_state.active_team)
+        else {
+            break;
+        };
+        play_turn(agents[index].as_mut(), scenario_state);
+    }
+    scenario_state.outcome()
+}
+
+#[cfg(test)]
+mod tests {
+    use advance_craft_engine::{
+        dev_helpers::new_scenario_with_units, rules::GameRules, units::UnitType,
+    };
+
+    use super::*;
+
+    /// Never does anything.
+    struct Idle;
+
+    impl Agent for Idle {
+        fn choose_commands(&mut self, _view: &ScenarioState, _team: TeamID) -> Vec<Command> {
+            vec![]
+        }
+    }
+
+    fn agents() -> Vec<Box<dyn Agent>> {
+        vec![Box::new(Idle), Box::new(Idle)]
+    }
+
+    #[test]
+    fn turns_always_end() {
+        let mut state = new_scenario_with_units(GameRules::default(), &[]);
+
+        let results = play_turn(&mut Idle, &mut state);
+
+        assert!(matches!(results.as_slice(), [(Command::EndTurn, _)]));
+        assert_eq!(state.active_team, 1);
+    }
+
+    #[test]
+    fn games_stop_at_the_turn_limit_or_a_winner() {
+        let mut state = new_scenario_with_units(GameRules::default(), &[]);
+        assert_eq!(
+            play_game(&mut agents(), &mut state, 5),
+            GameOutcome::Ongoing
+        );
+
+        let mut state =
+            new_scenario_with_units(GameRules::default(), &[(UnitType::Marine, (0, 0), 0)]);
+        state.structures.clear();
+        let turn = state.turn;
+        assert_eq!(
+            play_game(&mut agents(), &mut state, 5),
+            GameOutcome::Winner(0)
+        );
+        assert_eq!(state.turn, turn);
+    }
+}
@@ -0,0 +1,269 @@
+use advance_craft_engine::{Command, ScenarioState, TeamID, Tile, Unit, UnitId, rules::Rng};
+use serde::{Deserialize, Serialize};
+
+use super::agent::Agent;
+
+/// Funds are worth less than units, since they still have to be spent.
+const FUNDS_WEIGHT: f32 = 0.5;
+/// Score lost per tile between a healthy unit and the nearest enemy.
+const ADVANCE_WEIGHT: f32 = 2.0;
+/// Score lost per tile between a damaged unit and the nearest friendly structure.
+const RETREAT_WEIGHT: f32 = 5.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+pub struct DifficultyStats {
+    pub mistake_chance: f32, // Chance of picking a random command instead of the best one
+    pub retreat_below: f32,  // Fraction of max health below which units head home to heal
+}
+
+impl Difficulty {
+    pub const fn value(&self) -> DifficultyStats {
+        match self {
+            Difficulty::Easy => DifficultyStats {
+                mistake_chance: 0.4,
+                retreat_below: 0.0,
+            },
+            Difficulty::Normal => DifficultyStats {
+                mistake_chance: 0.15,
+                retreat_below: 0.3,
+            },
+            Difficulty::Hard => DifficultyStats {
+                mistake_chance: 0.0,
+                retreat_below: 0.4,
+            },
+        }
+    }
+}
+
+/// Gives each unit in turn whichever command leaves the team best off straight away, then
+/// spends what's left on the most expensive units it can build.
+pub struct GreedyAgent {
+    pub difficulty: Difficulty,
+    rng: Rng,
+}
+
+impl GreedyAgent {
+    pub fn new(difficulty: Difficulty, seed: u64) -> GreedyAgent {
+        GreedyAgent {
+            difficulty,
+            rng: Rng::new(seed),
+        }
+    }
+
+    fn choose_unit_command(
+        &mut self,
+        state: &ScenarioState,
+        team: TeamID,
+        unit_id: UnitId,
+    ) -> Option<Command> {
+        let options = state.legal_commands_for_unit(unit_id);
+        if options.is_empty() {
+            return None;
+        }
+
+        if self.rng.next_f32() < self.difficulty.value().mistake_chance {
+            let index = self.rng.next_u64() as usize % options.len();
+            return Some(options[index].clone());
+        }
+
+        let mut best: Option<(f32, Command)> = None;
+        for command in options {
+            let mut after = state.clone();
+            after.execute(command.clone());
+            let score = self.score(&after, team, unit_id);
+            if best
+                .as_ref()
+                .is_none_or(|(best_score, _)| score > *best_score)
+            {
+                best = Some((score, command));
+            }
+        }
+        best.map(|(_, command)| command)
+    }
+
+    fn choose_build(&self, state: &ScenarioState, team: TeamID) -> Option<Command> {
+        state
+            .structures
+            .iter()
+            .filter(|structure| structure.team == team)
+            .flat_map(|structure| state.legal_commands_for_structure(structure.id))
+            .filter_map(|command| match command {
+                Command::Build { unit_type, .. } => Some((unit_type.value().cost, command)),
+                _ => None,
+            })
+            .max_by_key(|(cost, _)| *cost)
+            .map(|(_, command)| command)
+    }
+
+    fn score(&self, state: &ScenarioState, team: TeamID, unit_id: UnitId) -> f32 {
+        let mut score = material(state, team);
+        let Some(unit) = state.get_unit(unit_id) else {
+            return score;
+        };
+
+        // There's nothing to capture yet, so healthy units press towards the enemy instead.
+        let health = unit.health / state.get_max_health(unit);
+        if health < self.difficulty.value().retreat_below {
+            let home = state
+                .structures
+                .iter()
+                .filter(|structure| structure.team == team && structure.is_complete())
+                .map(|structure| structure.position);
+            if let Some(distance) = nearest(state, &unit.position, home) {
+                score -= distance * RETREAT_WEIGHT;
+            }
+        } else {
+            let enemy_units = state
+                .units
+                .iter()
+                .filter(|other| other.team != team)
+                .map(|other| other.position);
+            let enemy_structures = state
+                .structures
+                .iter()
+                .filter(|structure| structure.team != team)
+                .map(|structure| structure.position);
+            if let Some(distance) =
+                nearest(state, &unit.position, enemy_units.chain(enemy_structures))
+            {
+                score -= distance * ADVANCE_WEIGHT;
+            }
+        }
+        score
+    }
+}
+
+impl Agent for GreedyAgent {
+    fn choose_commands(&mut self, view: &ScenarioState, team: TeamID) -> Vec<Command> {
+        let mut state = view.clone();
+        let mut commands = vec![];
+
+        let unit_ids: Vec<UnitId> = state
+            .units
+            .iter()
+            .filter(|unit| unit.team == team)
+            .map(|unit| unit.id)
+            .collect();
+        for unit_id in unit_ids {
+            if let Some(command) = self.choose_unit_command(&state, team, unit_id) {
+                state.execute(command.clone());
+                commands.push(command);
+            }
+        }
+
+        // Build last, once units have moved off the production tiles.
+        while let Some(command) = self.choose_build(&state, team) {
+            state.execute(command.clone());
+            commands.push(command);
+        }
+
+        commands.push(Command::EndTurn);
+        commands
+    }
+}
+
+fn unit_value(state: &ScenarioState, unit: &Unit) -> f32 {
+    let stats = unit.unit_type.value();
+    let max = state.get_max_health(unit) + stats.max_shields;
+    let cargo: f32 = unit
+        .cargo
+        .iter()
+        .map(|cargo| unit_value(state, cargo))
+        .sum();
+    stats.cost as f32 * (unit.health + unit.shields) / max + cargo
+}
+
+/// The value of everything the team has, minus the value of everything its enemies have.
+pub fn material(state: &ScenarioState, team: TeamID) -> f32 {
+    let sign = |owner: TeamID| if owner == team { 1.0 } else { -1.0 };
+
+    let units: f32 = state
+        .units
+        .iter()
+        .map(|unit| sign(unit.team) * unit_value(state, unit))
+        .sum();
+    let structures: f32 = state
+        .structures
+        .iter()
+        .map(|structure| {
+            let stats = structure.structure_type.value();
+            sign(structure.team) * stats.cost as f32 * structure.health / stats.max_health
+        })
+        .sum();
+    let funds: f32 = state
+        .team_states
+        .iter()
+        .map(|(owner, team_state)| {
+            let network: f32 = team_state
+                .nydus_network
+                .iter()
+                .map(|unit| unit_value(state, unit))
+                .sum();
+            sign(*owner) * (team_state.funds as f32 * FUNDS_WEIGHT + network)
+        })
+        .sum();
+
+    units + structures + funds
+}
+
+fn nearest(state: &ScenarioState, from: &Tile, tiles: impl Iterator<Item = Tile>) -> Option<f32> {
+    tiles
+        .map(|tile| state.distance(from, &tile))
+        .min_by(|a, b| a.total_cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use advance_craft_engine::{
+        CommandStatus, dev_helpers::new_scenario_state, dev_helpers::new_scenario_with_units,
+        rules::GameRules, units::UnitType,
+    };
+
+    use super::*;
+    use crate::agent::play_turn;
+
+    #[test]
+    fn material_is_zero_sum() {
+        let state = new_scenario_state(GameRules::default());
+        assert_eq!(material(&state, 0), -material(&state, 1));
+    }
+
+    #[test]
+    fn hard_agents_attack_when_they_can() {
+        let mut state = new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::Marine, (2, 0), 0),
+                (UnitType::Zergling, (3, 0), 1),
+            ],
+        );
+
+        let results = play_turn(&mut GreedyAgent::new(Difficulty::Hard, 0), &mut state);
+
+        assert!(matches!(results[0].0, Command::Attack { .. }));
+        let zergling = state.get_unit(1).unwrap();
+        assert!(zergling.health < state.get_max_health(zergling));
+    }
+
+    #[test]
+    fn agents_only_play_legal_commands() {
+        let mut state = new_scenario_state(GameRules::default());
+        let mut agents = [
+            GreedyAgent::new(Difficulty::Hard, 1),
+            GreedyAgent::new(Difficulty::Easy, 2),
+        ];
+
+        for turn in 0..10 {
+            let agent = &mut agents[turn % 2];
+            for (command, result) in play_turn(agent, &mut state) {
+                assert_eq!(result.status(), CommandStatus::Ok, "{:?}", command);
+            }
+        }
+    }
+}
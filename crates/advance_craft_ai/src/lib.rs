@@ -0,0 +1,7 @@
+pub mod agent;
+pub mod greedy;
+pub mod mcts;
+
+pub use agent::*;
+pub use greedy::*;
+pub use mcts::*;
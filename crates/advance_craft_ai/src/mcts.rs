@@ -0,0 +1,371 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use advance_craft_engine::{
+    Command, CommandStatus, ScenarioState, TeamID, TerrainType, Tile, Unit, UnitId,
+    rules::{GameOutcome, Rng},
+    units::air::Layer,
+};
+
+use super::{agent::Agent, greedy::material};
+
+/// Scores a state for a team. Higher is better, and the scale doesn't matter as long as it
+/// matches `MctsConfig::value_scale`.
+pub trait Evaluator: Send + Sync {
+    fn evaluate(&self, state: &ScenarioState, team: TeamID) -> f32;
+}
+
+/// Scores a state by the value of what each side has left.
+pub struct MaterialEvaluator;
+
+impl Evaluator for MaterialEvaluator {
+    fn evaluate(&self, state: &ScenarioState, team: TeamID) -> f32 {
+        material(state, team)
+    }
+}
+
+/// How long to search. Iterations are per command, time is for the whole turn.
+#[derive(Clone, Copy, Debug)]
+pub enum Budget {
+    Iterations(u32),
+    Time(Duration),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MctsConfig {
+    pub budget: Budget,
+    pub exploration: f32,    // UCT exploration constant
+    pub max_depth: usize,    // How many commands ahead each search looks
+    pub max_commands: usize, // The most commands in one turn, before the turn is ended
+    pub value_scale: f32,    // Evaluation lead that counts as a fairly safe win
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            budget: Budget::Time(Duration::from_secs(1)),
+            exploration: 1.4,
+            max_depth: 8,
+            max_commands: 64,
+            value_scale: 500.0,
+        }
+    }
+}
+
+struct Node {
+    command: Option<Command>, // None for the root
+    children: Vec<usize>,
+    untried: Option<Vec<Command>>, // Filled in the first time the node is reached
+    visits: u32,
+    total_value: f32,
+}
+
+impl Node {
+    fn new(command: Option<Command>) -> Node {
+        Node {
+            command,
+            children: vec![],
+            untried: None,
+            visits: 0,
+            total_value: 0.0,
+        }
+    }
+}
+
+/// The choices at one point in a turn. Units are given their orders one at a time, in a fixed
+/// order, and structures go last, so each node only branches over one unit's commands rather
+/// than the whole team's.
+fn next_commands(state: &ScenarioState, team: TeamID) -> Vec<Command> {
+    for unit_id in state.units_pending_action(team) {
+        let commands = state.legal_commands_for_unit(unit_id);
+        if !commands.is_empty() {
+            return commands;
+        }
+    }
+
+    let mut commands: Vec<Command> = state
+        .structures
+        .iter()
+        .filter(|structure| structure.team == team)
+        .flat_map(|structure| state.legal_commands_for_structure(structure.id))
+        .collect();
+    commands.push(Command::EndTurn);
+    commands
+}
+
+/// Picks each command of its turn with a Monte Carlo tree search over the commands that could
+/// follow it, scoring the end of each line with an `Evaluator`.
+///
+/// Each iteration plays on a different guess at what fog hides: enemies seen on earlier turns
+/// are placed somewhere out of sight near where they were last seen, and luck is rerolled.
+/// Tree nodes store commands rather than states, so the same tree serves every guess.
+pub struct MctsAgent {
+    pub config: MctsConfig,
+    evaluator: Box<dyn Evaluator>,
+    rng: Rng,
+    last_seen: HashMap<UnitId, Unit>,
+}
+
+impl MctsAgent {
+    pub fn new(config: MctsConfig, evaluator: Box<dyn Evaluator>, seed: u64) -> MctsAgent {
+        MctsAgent {
+            config,
+            evaluator,
+            rng: Rng::new(seed),
+            last_seen: HashMap::new(),
+        }
+    }
+
+    /// Keeps track of enemies that have slipped back into the fog.
+    fn remember(&mut self, view: &ScenarioState, team: TeamID) {
+        self.last_seen.retain(|id, unit| {
+            view.get_unit(*id).is_none() && !view.is_in_vision(team, &unit.position)
+        });
+        for unit in view.units.iter().filter(|unit| unit.team != team) {
+            self.last_seen.insert(unit.id, unit.clone());
+        }
+    }
+
+    /// A complete state consistent with what the team can see.
+    fn determinize(&mut self, view: &ScenarioState, team: TeamID) -> ScenarioState {
+        let mut state = view.clone();
+        state.rng = Rng::new(self.rng.next_u64());
+
+        let mut hidden: Vec<&Unit> = self
+            .last_seen
+            .values()
+            .filter(|unit| view.get_unit(unit.id).is_none())
+            .collect();
+        hidden.sort_by_key(|unit| unit.id);
+
+        for unit in hidden {
+            let layer = unit.layer();
+            let reach = view.rules.movement_range as f32;
+            let options: Vec<Tile> = state
+                .tiles_in_range(unit.position, 0.0, reach)
+                .filter(|tile| {
+                    let passable = layer != Layer::Ground
+                        || !matches!(
+                            state.map[tile.y as usize][tile.x as usize],
+                            TerrainType::Water
+                        );
+                    passable
+                        && !state.is_in_vision(team, tile)
+                        && state.get_unit_at_layer(tile.x, tile.y, layer).is_none()
+                })
+                .collect();
+            if options.is_empty() {
+                continue;
+            }
+
+            let mut unit = unit.clone();
+            unit.position = options[self.rng.next_u64() as usize % options.len()];
+            state.units.push(unit);
+        }
+        state
+    }
+
+    /// Turns an evaluation into a value between 0 and 1.
+    fn value(&self, state: &ScenarioState, team: TeamID) -> f32 {
+        match state.outcome() {
+            GameOutcome::Winner(winner) => return if winner == team { 1.0 } else { 0.0 },
+            GameOutcome::Draw => return 0.5,
+            GameOutcome::Ongoing => {}
+        }
+
+        let score = self.evaluator.evaluate(state, team) / self.config.value_scale;
+        1.0 / (1.0 + (-score).exp())
+    }
+
+    fn select_child(&self, tree: &[Node], node: usize) -> usize {
+        let parent_visits = tree[node].visits.max(1) as f32;
+        let uct = |child: &Node| {
+            let visits = child.visits.max(1) as f32;
+            child.total_value / visits
+                + self.config.exploration * (parent_visits.ln() / visits).sqrt()
+        };
+
+        tree[node]
+            .children
+            .iter()
+            .copied()
+            .max_by(|a, b| uct(&tree[*a]).total_cmp(&uct(&tree[*b])))
+            .unwrap()
+    }
+
+    fn iterate(&mut self, tree: &mut Vec<Node>, view: &ScenarioState, team: TeamID) {
+        let mut state = self.determinize(view, team);
+        let mut path = vec![0];
+        let mut node = 0;
+
+        while state.active_team == team && path.len() <= self.config.max_depth {
+            if tree[node].untried.is_none() {
+                tree[node].untried = Some(next_commands(&state, team));
+            }
+
+            // Expand one untried command, then stop and evaluate.
+            let untried = tree[node].untried.as_mut().unwrap();
+            if !untried.is_empty() {
+                let index = self.rng.next_u64() as usize % untried.len();
+                let command = untried.swap_remove(index);
+                if !matches!(state.execute(command.clone()).status(), CommandStatus::Ok) {
+                    // Only legal under a different guess at the hidden units.
+                    break;
+                }
+
+                tree.push(Node::new(Some(command)));
+                let child = tree.len() - 1;
+                tree[node].children.push(child);
+                path.push(child);
+                break;
+            }
+
+            if tree[node].children.is_empty() {
+                break;
+            }
+
+            node = self.select_child(tree, node);
+            let command = tree[node].command.clone().unwrap();
+            if !matches!(state.execute(command).status(), CommandStatus::Ok) {
+                break;
+            }
+            path.push(node);
+        }
+
+        let value = self.value(&state, team);
+        for index in path {
+            tree[index].visits += 1;
+            tree[index].total_value += value;
+        }
+    }
+
+    /// The best next command for the team, or None if nothing it tried works in the view.
+    fn search(&mut self, view: &ScenarioState, team: TeamID, budget: Budget) -> Option<Command> {
+        let mut tree = vec![Node::new(None)];
+        let start = Instant::now();
+        let mut iterations = 0;
+        loop {
+            let done = match budget {
+                Budget::Iterations(max) => iterations >= max,
+                Budget::Time(limit) => start.elapsed() >= limit,
+            };
+            // Always search a little, even when the turn is out of time.
+            if done && iterations > 0 {
+                break;
+            }
+            self.iterate(&mut tree, view, team);
+            iterations += 1;
+        }
+
+        // The most visited command, skipping any that only worked because of a guessed unit.
+        let mut children = tree[0].children.clone();
+        children.sort_by_key(|child| std::cmp::Reverse(tree[*child].visits));
+        children
+            .into_iter()
+            .filter_map(|child| tree[child].command.clone())
+            .find(|command| view.is_legal(command))
+    }
+}
+
+impl Agent for MctsAgent {
+    fn choose_commands(&mut self, view: &ScenarioState, team: TeamID) -> Vec<Command> {
+        self.remember(view, team);
+
+        let start = Instant::now();
+        let mut state = view.clone();
+        let mut commands = vec![];
+        while state.active_team == team && commands.len() < self.config.max_commands {
+            // Share what's left of the turn's time between the decisions still to make.
+            let budget = match self.config.budget {
+                Budget::Iterations(max) => Budget::Iterations(max),
+                Budget::Time(limit) => {
+                    let decisions = state.units_pending_action(team).len() as u32 + 1;
+                    Budget::Time(limit.saturating_sub(start.elapsed()) / decisions)
+                }
+            };
+
+            let Some(command) = self.search(&state, team, budget) else {
+                break;
+            };
+            state.execute(command.clone());
+            commands.push(command);
+        }
+
+        if state.active_team == team {
+            commands.push(Command::EndTurn);
+        }
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use advance_craft_engine::{
+        Command, dev_helpers::new_scenario_with_units, rules::GameRules, units::UnitType,
+    };
+
+    use super::*;
+    use crate::agent::plan_turn;
+
+    fn agent(budget: Budget) -> MctsAgent {
+        let config = MctsConfig {
+            budget,
+            ..Default::default()
+        };
+        MctsAgent::new(config, Box::new(MaterialEvaluator), 0)
+    }
+
+    fn unit_id(command: &Command) -> Option<UnitId> {
+        match command {
+            Command::Attack { attacker_id, .. } | Command::AttackGround { attacker_id, .. } => {
+                Some(*attacker_id)
+            }
+            Command::Wait { unit_id } | Command::Act { unit_id, .. } => Some(*unit_id),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn units_act_at_most_once_per_turn() {
+        let state = new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::SiegeTank, (0, 0), 0),
+                (UnitType::Marine, (1, 1), 0),
+                (UnitType::Roach, (3, 0), 1),
+                (UnitType::Zergling, (3, 1), 1),
+            ],
+        );
+
+        let commands = plan_turn(&mut agent(Budget::Iterations(20)), &state);
+
+        let mut acted: Vec<UnitId> = commands.iter().filter_map(unit_id).collect();
+        let actions = acted.len();
+        acted.sort();
+        acted.dedup();
+        assert_eq!(acted.len(), actions, "{:?}", commands);
+        assert!(commands.len() <= 2 * state.units.len() + state.structures.len() + 1);
+        assert!(matches!(commands.last(), Some(Command::EndTurn)));
+    }
+
+    #[test]
+    fn turns_are_capped() {
+        let state = new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::Marine, (0, 0), 0),
+                (UnitType::Marine, (1, 1), 0),
+                (UnitType::Roach, (3, 0), 1),
+            ],
+        );
+        let mut agent = agent(Budget::Iterations(5));
+        agent.config.max_commands = 1;
+
+        let commands = plan_turn(&mut agent, &state);
+
+        assert!(commands.len() <= 2, "{:?}", commands);
+        assert!(matches!(commands.last(), Some(Command::EndTurn)));
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{CommandErr, CommandResult, CommandStatus, ScenarioState, Tile, UnitId, units::*};
+
+/// What a unit does once it reaches the end of its path.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum ActAction {
+    Attack(UnitId),
+    AttackGround(Tile),
+    Repair(RepairTarget),
+    Heal(UnitId),
+    Wait,
+}
+
+impl ScenarioState {
+    /// Moves a unit along `path` and then carries out `action` from where it ends up, as a
+    /// single command. Nothing changes unless the whole sequence is valid, except when the
+    /// unit is ambushed on the way, which stops it where it was caught.
+    pub(crate) fn act(
+        &mut self,
+        unit_id: UnitId,
+        path: Vec<Tile>,
+        action: ActAction,
+    ) -> CommandResult {
+        let error = |err: CommandErr| CommandResult::Act {
+            status: CommandStatus::Err(err),
+            tiles: vec![],
+            trapped_by: None,
+            action_result: None,
+        };
+
+        if self.get_unit(unit_id).is_none() {
+            return error(CommandErr::InvalidTarget);
+        }
+
+        if !path.is_empty()
+            && let Err(err) = self.check_path(unit_id, &path)
+        {
+            return error(err);
+        }
+
+        // Work on a copy, which only replaces the real state once everything has succeeded.
+        let mut state = self.clone();
+
+        let mut tiles = vec![];
+        if let Some(destination) = path.last().copied() {
+            let CommandResult::Move {
+                status,
+                tiles: moved,
+                trapped_by,
+            } = state.unit_move(unit_id, path)
+            else {
+                unreachable!("unit_move always returns CommandResult::Move");
+            };
+
+            if let CommandStatus::Err(err) = status {
+                return error(err);
+            }
+
+            // The ambush happens whatever the player meant to do next, so it can't be cancelled.
+            if trapped_by.is_some() {
+                *self = state;
+                return CommandResult::Act {
+                    status: CommandStatus::Partial,
+                    tiles: moved,
+                    trapped_by,
+                    action_result: None,
+                };
+            }
+
+            if state.get_unit(unit_id).unwrap().position != destination {
+                return error(CommandErr::PathBlocked);
+            }
+            tiles = moved;
+        }
+
+        let action_result = match action {
+            ActAction::Attack(defender_id) => Some(state.attack(unit_id, defender_id)),
+            ActAction::AttackGround(tile) => Some(state.attack_ground(unit_id, tile)),
+            ActAction::Repair(target) => Some(state.repair(unit_id, target)),
+            ActAction::Heal(target_id) => Some(state.heal(unit_id, target_id)),
+            ActAction::Wait => Some(state.wait(unit_id)),
+        };
+
+        if let Some(CommandStatus::Err(err)) = action_result.as_ref().map(|result| result.status())
+        {
+            return error(err);
+        }
+
+        *self = state;
+        CommandResult::Act {
+            status: CommandStatus::Ok,
+            tiles,
+            trapped_by: None,
+            action_result: action_result.map(Box::new),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, CommandErr, GameRules, dev_helpers::new_scenario_with_units};
+
+    fn state() -> ScenarioState {
+        new_scenario_with_units(
+            GameRules::default(),
+            &[(UnitType::Marine, (0, 0), 0), (UnitType::Roach, (3, 0), 1)],
+        )
+    }
+
+    #[test]
+    fn acting_along_a_broken_path_changes_nothing() {
+        let mut state = state();
+        let before = state.clone();
+
+        let result = state.execute(Command::Act {
+            unit_id: 0,
+            path: vec![Tile { x: 2, y: 0 }],
+            action: ActAction::Attack(1),
+        });
+
+        assert_eq!(result.status(), CommandStatus::Err(CommandErr::InvalidPath));
+        assert_eq!(state.units, before.units);
+    }
+
+    #[test]
+    fn a_failed_action_undoes_the_move() {
+        let mut state = state();
+        let before = state.clone();
+
+        let result = state.execute(Command::Act {
+            unit_id: 0,
+            path: vec![Tile { x: 0, y: 1 }],
+            action: ActAction::Heal(1),
+        });
+
+        assert!(matches!(result.status(), CommandStatus::Err(_)));
+        assert_eq!(state.units, before.units);
+    }
+
+    #[test]
+    fn acting_moves_then_acts() {
+        let mut state = state();
+
+        let result = state.execute(Command::Act {
+            unit_id: 0,
+            path: vec![Tile { x: 1, y: 0 }, Tile { x: 2, y: 0 }],
+            action: ActAction::Attack(1),
+        });
+
+        assert_eq!(result.status(), CommandStatus::Ok);
+        let marine = state.get_unit(0).unwrap();
+        assert_eq!(marine.position, Tile { x: 2, y: 0 });
+        assert!(marine.has_attacked);
+        assert!(state.get_unit(1).unwrap().health < UnitType::Roach.value().max_health);
+    }
+}
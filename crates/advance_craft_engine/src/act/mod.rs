@@ -0,0 +1,3 @@
+pub mod act;
+
+pub use act::*;
@@ -1,31 +1,69 @@
+use std::{collections::HashMap, sync::Arc};
+
 use crate::{
-    Creep, ScenarioMap, ScenarioState, Structure, StructureId, StructureType, TerrainType, Tile,
-    Unit, UnitId, UnitType,
+    Creep, GameRules, Rng, ScenarioMap, ScenarioState, Structure, StructureId, StructureType,
+    TeamID, TeamState, TerrainType, Tile, UndoStack, Unit, UnitId, UnitType,
 };
 
-pub fn new_scenario_state() -> ScenarioState {
+pub fn new_scenario_state(rules: GameRules) -> ScenarioState {
     let map = create_map();
     let units = create_units();
     let structures = create_structures();
     let creep = create_creep(&map);
+    let teams = vec![0, 1];
+    let team_states = create_team_states(&teams, rules.starting_funds);
 
     ScenarioState {
-        map,
+        map: Arc::new(map),
         units,
         structures,
         active_team: 0,
-        teams: vec![0, 1],
+        teams,
         creep,
+        team_states,
+        rng: Rng::new(rules.seed),
+        rules,
+        turn: 1,
+        undo_stack: UndoStack::default(),
     }
 }
 
+/// The dev scenario's map and structures with only the given units on it, numbered in order.
+pub fn new_scenario_with_units(
+    rules: GameRules,
+    units: &[(UnitType, (u32, u32), TeamID)],
+) -> ScenarioState {
+    let mut state = new_scenario_state(rules);
+    state.units = units
+        .iter()
+        .enumerate()
+        .map(|(i, (unit_type, (x, y), team))| {
+            Unit::new(i as UnitId, *unit_type, Tile { x: *x, y: *y }, *team)
+        })
+        .collect();
+    state
+}
+
+fn create_team_states(teams: &[TeamID], starting_funds: u32) -> HashMap<TeamID, TeamState> {
+    teams
+        .iter()
+        .map(|team| {
+            let team_state = TeamState {
+                funds: starting_funds,
+                ..Default::default()
+            };
+            (*team, team_state)
+        })
+        .collect()
+}
+
 fn create_map() -> ScenarioMap {
     let mut number_terrain_map = vec![
         vec![1, 1, 1, 1, 1, 1, 1, 1, 1],
         vec![1, 1, 1, 1, 1, 1, 1, 1, 1],
         vec![1, 1, 1, 1, 1, 1, 1, 1, 1],
-        vec![1, 1, 1, 1, 1, 1, 1, 1, 1],
-        vec![1, 1, 1, 1, 1, 1, 1, 1, 1],
+        vec![1, 1, 1, 2, 1, 1, 1, 1, 1],
+        vec![1, 1, 1, 1, 1, 1, 3, 3, 1],
         vec![1, 1, 1, 1, 1, 1, 1, 1, 1],
     ];
     number_terrain_map.reverse();
@@ -37,6 +75,8 @@ fn create_map() -> ScenarioMap {
                 .map(|n| match n {
                     0 => TerrainType::Water,
                     1 => TerrainType::Grass,
+                    2 => TerrainType::Mountain,
+                    3 => TerrainType::HighGround,
                     _ => panic!("No terrain implemented for index {}", n),
                 })
                 .collect()
@@ -60,16 +100,15 @@ fn create_structures() -> Vec<Structure> {
         .map(|(i, data)| {
             let (structure_type, location, team) = data;
 
-            Structure {
-                id: i as StructureId,
+            Structure::new(
+                i as StructureId,
                 structure_type,
-                position: Tile {
+                Tile {
                     x: location.0,
                     y: location.1,
                 },
                 team,
-                health: structure_type.value().max_health,
-            }
+            )
         })
         .collect();
 
@@ -97,18 +136,15 @@ fn create_units() -> Vec<Unit> {
         .map(|(i, data)| {
             let (unit_type, location, team) = data;
 
-            Unit {
-                id: i as UnitId,
-                unit_type: unit_type,
-                position: Tile {
+            Unit::new(
+                i as UnitId,
+                unit_type,
+                Tile {
                     x: location.0,
                     y: location.1,
                 },
                 team,
-                health: unit_type.value().max_health,
-                has_moved: false,
-                has_attacked: false,
-            }
+            )
         })
         .collect();
     units
@@ -0,0 +1,134 @@
+use crate::{Rng, ScenarioState, TeamID, Tile, Unit, units::SPOTTING_RANGE};
+
+impl ScenarioState {
+    /// Whether the team can see the tile. Everything is in view when fog is off.
+    /// Units and structures see as far as they can spot for indirect fire.
+    pub fn is_in_vision(&self, team: TeamID, tile: &Tile) -> bool {
+        if !self.rules.fog {
+            return true;
+        }
+
+        let seen_by_unit = self
+            .units
+            .iter()
+            .any(|unit| unit.team == team && self.distance(&unit.position, tile) <= SPOTTING_RANGE);
+        let seen_by_structure = self.structures.iter().any(|structure| {
+            structure.team == team
+                && structure
+                    .tiles()
+                    .iter()
+                    .any(|structure_tile| self.distance(structure_tile, tile) <= SPOTTING_RANGE)
+        });
+        seen_by_unit || seen_by_structure
+    }
+
+    /// The state as seen by `team`, with anything hidden from them removed.
+    pub fn view_for_team(&self, team: TeamID) -> ScenarioState {
+        let mut view = self.clone();
+
+        view.units.retain(|unit| self.is_visible_to(unit, team));
+
+        // Enemies can see a transport, but not what it is carrying.
+        for unit in view.units.iter_mut().filter(|unit| unit.team != team) {
+            unit.cargo.clear();
+        }
+        // Completed upgrades show on the units, but not what an enemy has saved or is working on.
+        for (_, team_state) in view.team_states.iter_mut().filter(|(id, _)| **id != team) {
+            team_state.funds = 0;
+            team_state.research_queue.clear();
+            team_state.nydus_network.clear();
+        }
+        // Earlier states could show anything, and the generator would give away future luck.
+        view.undo_stack.clear();
+        view.rng = Rng::default();
+
+        view
+    }
+
+    pub fn is_visible_to(&self, unit: &Unit, team: TeamID) -> bool {
+        unit.team == team
+            || (self.is_in_vision(team, &unit.position)
+                && (!unit.requires_detection() || self.is_detected(unit, team)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GameRules, TargetStatus, UnitType,
+        dev_helpers::new_scenario_with_units,
+        status_effects::{StatusEffect, StatusEffectType},
+    };
+
+    fn state() -> ScenarioState {
+        let rules = GameRules {
+            seed: 7,
+            ..Default::default()
+        };
+        new_scenario_with_units(
+            rules,
+            &[
+                (UnitType::Marine, (4, 0), 0),
+                (UnitType::Roach, (5, 0), 1),
+                (UnitType::Roach, (6, 0), 1),
+            ],
+        )
+    }
+
+    #[test]
+    fn undetected_enemies_are_hidden() {
+        let mut state = state();
+        state.apply_status_effect(
+            1,
+            StatusEffect {
+                effect_type: StatusEffectType::Burrowed,
+                turns_remaining: None,
+            },
+        );
+
+        let view = state.view_for_team(0);
+
+        assert!(view.get_unit(1).is_none());
+        assert!(view.get_unit(2).is_some());
+        assert!(state.view_for_team(1).get_unit(1).is_some());
+    }
+
+    #[test]
+    fn detectors_reveal_burrowed_enemies() {
+        let mut state = state();
+        state.apply_status_effect(1, StatusEffect::permanent(StatusEffectType::Burrowed));
+        assert_eq!(state.target_status(0, 1), TargetStatus::NotDetected);
+
+        let overlord = Unit::new(3, UnitType::Overlord, Tile { x: 6, y: 1 }, 0);
+        state.units.push(overlord);
+        assert!(state.view_for_team(0).get_unit(1).is_some());
+        assert_eq!(state.target_status(0, 1), TargetStatus::Hittable);
+    }
+
+    #[test]
+    fn bases_detect_burrowed_enemies_nearby() {
+        let mut state = state();
+        let roach = state.units.iter_mut().find(|unit| unit.id == 1).unwrap();
+        roach.position = Tile { x: 3, y: 0 };
+        state.apply_status_effect(1, StatusEffect::permanent(StatusEffectType::Burrowed));
+        assert_eq!(state.target_status(0, 1), TargetStatus::Hittable);
+
+        for structure in state.structures.iter_mut() {
+            structure.construction_turns_remaining = 1;
+        }
+        assert_eq!(state.target_status(0, 1), TargetStatus::NotDetected);
+    }
+
+    #[test]
+    fn enemy_plans_and_luck_are_hidden() {
+        let mut state = state();
+        state.team_states.get_mut(&1).unwrap().funds = 1000;
+
+        let view = state.view_for_team(0);
+
+        assert_eq!(view.team_states[&1].funds, 0);
+        assert_eq!(view.team_states[&0].funds, state.team_states[&0].funds);
+        assert_ne!(view.rng, state.rng);
+    }
+}
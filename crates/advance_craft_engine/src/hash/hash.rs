@@ -0,0 +1,117 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::ScenarioState;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64 bit FNV-1a. Unlike `std::hash::DefaultHasher` it gives the same answer on every machine
+/// and Rust version, so hashes can be compared between server and clients.
+#[derive(Clone, Copy, Debug)]
+pub struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(FNV_OFFSET_BASIS)
+    }
+}
+
+impl Fnv {
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Write for Fnv {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn hash_of(value: &impl Serialize) -> u64 {
+    let mut fnv = Fnv::default();
+    // Writing to the hasher can't fail, and neither can serializing engine types.
+    serde_json::to_writer(&mut fnv, value).expect("Failed to hash value");
+    fnv.finish()
+}
+
+/// Combines hashes so that the order of the items doesn't matter.
+fn hash_unordered<T: Serialize>(items: impl Iterator<Item = T>) -> u64 {
+    items
+        .map(|item| hash_of(&item))
+        .fold(0, |total, hash| total.wrapping_add(hash))
+}
+
+impl ScenarioState {
+    /// A hash of everything that changes during a game, used to check that two copies of the
+    /// state agree. The order units and structures are stored in doesn't affect it.
+    ///
+    /// This covers the random number generator too, so on the authoritative state it also
+    /// catches copies whose luck has drifted. A team's view has the generator replaced (see
+    /// `view_for_team`), so the hashes servers send clients only cover what that team can see.
+    pub fn state_hash(&self) -> u64 {
+        let team_states = self.team_states.iter().map(|(team, team_state)| {
+            let mut upgrades: Vec<_> = team_state.upgrades.iter().collect();
+            upgrades.sort();
+            (
+                team,
+                team_state.funds,
+                upgrades,
+                &team_state.research_queue,
+                hash_unordered(team_state.nydus_network.iter()),
+            )
+        });
+
+        hash_of(&(
+            hash_unordered(self.units.iter()),
+            hash_unordered(self.structures.iter()),
+            hash_unordered(self.creep.0.iter()),
+            hash_unordered(team_states),
+            self.active_team,
+            self.turn,
+            self.rng,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{GameRules, dev_helpers::new_scenario_state};
+
+    #[test]
+    fn hashes_ignore_storage_order() {
+        let state = new_scenario_state(GameRules::default());
+        let mut reordered = state.clone();
+        reordered.units.reverse();
+        reordered.structures.reverse();
+        let mut teams: Vec<_> = state.team_states.clone().into_iter().collect();
+        teams.sort_by_key(|(team, _)| std::cmp::Reverse(*team));
+        reordered.team_states = HashMap::new();
+        for (team, team_state) in teams {
+            reordered.team_states.insert(team, team_state);
+        }
+
+        assert_eq!(reordered.state_hash(), state.state_hash());
+    }
+
+    #[test]
+    fn hashes_change_with_the_state() {
+        let state = new_scenario_state(GameRules::default());
+        let mut changed = state.clone();
+        changed.units[0].health -= 1.0;
+
+        assert_ne!(changed.state_hash(), state.state_hash());
+    }
+}
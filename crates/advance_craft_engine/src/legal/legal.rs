@@ -0,0 +1,448 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    Command, CommandErr, CommandStatus, ResearchType, ScenarioState, StructureId, StructureType,
+    TeamID, TerrainType, Tile, UnitId, UnitType,
+    act::ActAction,
+    units::{weapon::Delivery, *},
+};
+
+impl ScenarioState {
+    /// Whether the command would succeed if executed now. Commands are checked by running them
+    /// on a copy of the state, so this always agrees with `execute`. The copy has nothing to undo
+    /// and doesn't record the command, so the check costs the same however long the turn has been.
+    pub fn is_legal(&self, command: &Command) -> bool {
+        let mut state = self.clone();
+        matches!(state.apply(command.clone()).status(), CommandStatus::Ok)
+    }
+
+    /// Every command the team could issue right now.
+    pub fn legal_commands(&self, team: TeamID) -> Vec<Command> {
+        if team != self.active_team {
+            return vec![];
+        }
+
+        let mut commands = vec![];
+        for unit in self.units.iter().filter(|unit| unit.team == team) {
+            commands.append(&mut self.legal_commands_for_unit(unit.id));
+        }
+        for structure in self.structures.iter().filter(|s| s.team == team) {
+            commands.append(&mut self.legal_commands_for_structure(structure.id));
+        }
+        commands.push(Command::EndTurn);
+        commands
+    }
+
+    /// Checks that the unit could walk `path`: one tile at a time, over terrain it can cross,
+    /// and no further than its movement range. The path may start with the unit's own tile.
+    /// Other units aren't checked, since an enemy in the way may be hidden.
+    pub fn check_path(&self, unit_id: UnitId, path: &[Tile]) -> Result<(), CommandErr> {
+        let Some(unit) = self.get_unit(unit_id) else {
+            return Err(CommandErr::InvalidTarget);
+        };
+        if path.is_empty() {
+            return Err(CommandErr::InvalidPath);
+        }
+
+        let steps = match path.first() {
+            Some(first) if *first == unit.position => &path[1..],
+            _ => path,
+        };
+        if steps.len() > self.get_movement_range(&unit_id) as usize {
+            return Err(CommandErr::OutOfRange);
+        }
+
+        let mut previous = unit.position;
+        for tile in steps {
+            let adjacent = previous.x.abs_diff(tile.x) + previous.y.abs_diff(tile.y) == 1;
+            if !adjacent
+                || self
+                    .is_tile_within_bounds(tile.x as i32, tile.y as i32)
+                    .is_err()
+            {
+                return Err(CommandErr::InvalidPath);
+            }
+
+            let terrain = self.map[tile.y as usize][tile.x as usize];
+            if unit.layer() == Layer::Ground && matches!(terrain, TerrainType::Water) {
+                return Err(CommandErr::InvalidPath);
+            }
+            previous = *tile;
+        }
+        Ok(())
+    }
+
+    /// Paths to every tile the unit can finish a move on, not including the tile it starts on.
+    pub fn reachable_paths(&self, unit_id: UnitId) -> Vec<Vec<Tile>> {
+        let Some(unit) = self.get_unit(unit_id) else {
+            return vec![];
+        };
+        if unit.has_moved || !unit.can_move() {
+            return vec![];
+        }
+
+        let range = self.get_movement_range(&unit_id) as usize;
+        let start = unit.position;
+        let mut paths: HashMap<(u32, u32), Vec<Tile>> = HashMap::new();
+        paths.insert((start.x, start.y), vec![]);
+
+        // Breadth first, so each tile is reached by one of its shortest paths.
+        let mut queue = VecDeque::from([start]);
+        while let Some(tile) = queue.pop_front() {
+            let path = paths[&(tile.x, tile.y)].clone();
+            if path.len() >= range {
+                continue;
+            }
+            for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let (x, y) = (tile.x as i32 + dx, tile.y as i32 + dy);
+                if paths.contains_key(&(x as u32, y as u32))
+                    || !self.is_tile_moveable(unit_id, x, y)
+                {
+                    continue;
+                }
+                let next = Tile {
+                    x: x as u32,
+                    y: y as u32,
+                };
+                let mut next_path = path.clone();
+                next_path.push(next);
+                paths.insert((next.x, next.y), next_path);
+                queue.push_back(next);
+            }
+        }
+
+        // Units can pass through friends, but not stop on top of them.
+        let mut paths: Vec<Vec<Tile>> = paths
+            .into_values()
+            .filter(|path| {
+                path.last()
+                    .is_some_and(|end| !self.is_tile_occupied(unit_id, end.x, end.y))
+            })
+            .collect();
+        // Keep the order stable, so the same state always gives the same commands.
+        paths.sort_by_key(|path| {
+            let end = path.last().unwrap();
+            (path.len(), end.y, end.x)
+        });
+        paths
+    }
+
+    /// Every command the unit could be given right now.
+    pub fn legal_commands_for_unit(&self, unit_id: UnitId) -> Vec<Command> {
+        let Some(unit) = self.get_unit(unit_id) else {
+            return vec![];
+        };
+        if unit.team != self.active_team {
+            return vec![];
+        }
+
+        let mut candidates = vec![Command::Wait { unit_id }];
+        let stats = unit.unit_type.value();
+
+        // Acting from where the unit stands
+        for action in self.candidate_actions(unit_id, &unit.position) {
+            candidates.push(match action {
+                ActAction::Attack(defender_id) => Command::Attack {
+                    attacker_id: unit_id,
+                    defender_id,
+                },
+                ActAction::AttackGround(tile) => Command::AttackGround {
+                    attacker_id: unit_id,
+                    tile,
+                },
+                ActAction::Repair(target) => Command::Repair {
+                    worker_id: unit_id,
+                    target,
+                },
+                ActAction::Heal(target_id) => Command::Heal {
+                    healer_id: unit_id,
+                    target_id,
+                },
+                ActAction::Wait => Command::Wait { unit_id },
+            });
+        }
+
+        // Moving, and moving then acting
+        for path in self.reachable_paths(unit_id) {
+            let end = *path.last().unwrap();
+            for action in self.candidate_actions(unit_id, &end) {
+                candidates.push(Command::Act {
+                    unit_id,
+                    path: path.clone(),
+                    action,
+                });
+            }
+            candidates.push(Command::Move {
+                unit_id,
+                tiles: path,
+            });
+        }
+
+        for into in UnitType::ALL {
+            if unit.unit_type.get_morph(into).is_some() {
+                candidates.push(Command::Morph { unit_id, into });
+            }
+        }
+
+        // Transports
+        for transport in self.units.iter().filter(|t| t.team == unit.team) {
+            if transport.unit_type.value().cargo_capacity > 0 {
+                candidates.push(Command::Load {
+                    unit_id,
+                    transport: Transport::Unit(transport.id),
+                });
+            }
+        }
+        for structure in self.structures.iter().filter(|s| s.team == unit.team) {
+            if structure.structure_type.value().cargo_capacity > 0 {
+                candidates.push(Command::Load {
+                    unit_id,
+                    transport: Transport::Structure(structure.id),
+                });
+            }
+        }
+        if stats.cargo_capacity > 0 {
+            let transport = Transport::Unit(unit_id);
+            for cargo in unit.cargo.iter() {
+                for tile in self.tiles_in_range(unit.position, 1.0, 1.0) {
+                    candidates.push(Command::Unload {
+                        transport,
+                        unit_id: cargo.id,
+                        tile,
+                    });
+                }
+            }
+        }
+
+        // Construction, anywhere the unit is close enough to build
+        let reach = StructureType::ALL
+            .iter()
+            .map(|structure_type| {
+                let (width, height) = structure_type.value().footprint;
+                width.max(height)
+            })
+            .max()
+            .unwrap_or(1) as f32;
+        for structure_type in StructureType::ALL {
+            for tile in self.tiles_in_range(unit.position, 0.0, reach) {
+                if self.check_placement(unit_id, structure_type, tile).is_ok() {
+                    candidates.push(Command::Construct {
+                        worker_id: unit_id,
+                        structure_type,
+                        tile,
+                    });
+                }
+            }
+        }
+
+        candidates
+            .into_iter()
+            .filter(|command| self.is_legal(command))
+            .collect()
+    }
+
+    /// Every command the structure could be given right now.
+    pub fn legal_commands_for_structure(&self, structure_id: StructureId) -> Vec<Command> {
+        let Some(structure) = self.get_structure(structure_id) else {
+            return vec![];
+        };
+        if structure.team != self.active_team {
+            return vec![];
+        }
+
+        let mut candidates = vec![];
+        for unit_type in UnitType::ALL {
+            if unit_type.value().produced_at == Some(structure.structure_type) {
+                candidates.push(Command::Build {
+                    structure_id,
+                    unit_type,
+                });
+            }
+        }
+        for research in ResearchType::ALL {
+            candidates.push(Command::Research {
+                structure_id,
+                research,
+            });
+        }
+
+        // Units inside a shared network can leave from any of its structures.
+        if structure.structure_type.value().cargo_capacity > 0 {
+            let transport = Transport::Structure(structure_id);
+            let exits: Vec<Tile> = structure
+                .tiles()
+                .iter()
+                .flat_map(|tile| self.tiles_in_range(*tile, 1.0, 1.0))
+                .collect();
+            for cargo in self.get_cargo(transport) {
+                for tile in exits.iter() {
+                    candidates.push(Command::Unload {
+                        transport,
+                        unit_id: cargo.id,
+                        tile: *tile,
+                    });
+                }
+            }
+        }
+
+        candidates
+            .into_iter()
+            .filter(|command| self.is_legal(command))
+            .collect()
+    }
+
+    /// Actions worth checking for a unit standing on `tile`. These still need validating.
+    fn candidate_actions(&self, unit_id: UnitId, tile: &Tile) -> Vec<ActAction> {
+        let unit = self.get_unit(unit_id).unwrap();
+        let stats = unit.unit_type.value();
+        let mut actions = vec![];
+
+        if let Some(weapon) = stats.weapon_one {
+            let (min, max) = match weapon.delivery {
+                Delivery::Melee => (1.0, 1.0),
+                Delivery::Ranged(min, max) => (min, max),
+                Delivery::Splash(splash) => splash.range,
+            };
+            match weapon.delivery {
+                Delivery::Splash(_) => {
+                    for target in self.tiles_in_range(*tile, min, max) {
+                        actions.push(ActAction::AttackGround(target));
+                    }
+                }
+                _ => {
+                    for target in self.units.iter().filter(|t| t.team != unit.team) {
+                        if self.in_range(tile, &target.position, min, max) {
+                            actions.push(ActAction::Attack(target.id));
+                        }
+                    }
+                }
+            }
+        }
+
+        match stats.support {
+            Some(Support::Repair(_)) => {
+                for target in self.units.iter().filter(|t| t.id != unit_id) {
+                    if self.in_range(tile, &target.position, 0.0, 1.0) {
+                        actions.push(ActAction::Repair(RepairTarget::Unit(target.id)));
+                    }
+                }
+                for structure in self.structures.iter() {
+                    if structure
+                        .tiles()
+                        .iter()
+                        .any(|structure_tile| self.in_range(tile, structure_tile, 0.0, 1.0))
+                    {
+                        actions.push(ActAction::Repair(RepairTarget::Structure(structure.id)));
+                    }
+                }
+            }
+            Some(Support::Heal(_)) => {
+                for target in self.units.iter().filter(|t| t.id != unit_id) {
+                    if self.in_range(tile, &target.position, 0.0, 1.0) {
+                        actions.push(ActAction::Heal(target.id));
+                    }
+                }
+            }
+            None => {}
+        }
+
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{GameRules, dev_helpers::new_scenario_with_units};
+
+    fn state() -> ScenarioState {
+        new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::Marine, (0, 0), 0),
+                (UnitType::SiegeTank, (0, 2), 0),
+                (UnitType::Roach, (3, 2), 1),
+            ],
+        )
+    }
+
+    fn walk(unit_id: UnitId, tiles: &[(u32, u32)]) -> Command {
+        Command::Move {
+            unit_id,
+            tiles: tiles.iter().map(|(x, y)| Tile { x: *x, y: *y }).collect(),
+        }
+    }
+
+    #[test]
+    fn units_cannot_teleport() {
+        let state = state();
+        assert!(!state.is_legal(&walk(0, &[(8, 0)])));
+        assert!(!state.is_legal(&walk(0, &[(1, 0), (1, 2)])));
+    }
+
+    #[test]
+    fn paths_must_fit_the_movement_range() {
+        let state = state();
+        let range = state.get_movement_range(&0);
+        let path: Vec<(u32, u32)> = (1..=range + 1).map(|x| (x, 0)).collect();
+
+        assert!(state.is_legal(&walk(0, &path[..range as usize])));
+        assert!(!state.is_legal(&walk(0, &path)));
+    }
+
+    #[test]
+    fn paths_may_start_on_the_units_tile() {
+        let state = state();
+        assert!(state.is_legal(&walk(0, &[(0, 0), (1, 0), (1, 1)])));
+    }
+
+    #[test]
+    fn ground_units_cannot_cross_water() {
+        let mut state = state();
+        let mut map = (*state.map).clone();
+        map[0][1] = TerrainType::Water;
+        state.map = Arc::new(map);
+
+        assert!(!state.is_legal(&walk(0, &[(1, 0), (2, 0)])));
+        assert!(state.is_legal(&walk(0, &[(0, 1), (1, 1), (2, 1)])));
+    }
+
+    #[test]
+    fn an_illegal_move_changes_nothing() {
+        let mut state = state();
+        let before = state.clone();
+
+        let result = state.execute(walk(0, &[(8, 0)]));
+
+        assert_eq!(result.status(), CommandStatus::Err(CommandErr::InvalidPath));
+        assert_eq!(state.units, before.units);
+    }
+
+    #[test]
+    fn every_generated_command_is_legal() {
+        let state = state();
+        for command in state.legal_commands(0) {
+            assert!(state.is_legal(&command), "{:?}", command);
+        }
+    }
+
+    #[test]
+    fn units_are_only_offered_one_attack() {
+        let mut state = state();
+        let attack = state
+            .legal_commands_for_unit(1)
+            .into_iter()
+            .find(|command| matches!(command, Command::AttackGround { .. }))
+            .expect("The tank should be able to fire");
+        state.execute(attack);
+
+        assert!(
+            !state
+                .legal_commands_for_unit(1)
+                .iter()
+                .any(|command| matches!(command, Command::AttackGround { .. }))
+        );
+    }
+}
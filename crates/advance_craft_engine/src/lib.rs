@@ -1,11 +1,29 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
+pub mod act;
 pub mod dev_helpers;
+pub mod fog;
+pub mod hash;
+pub mod legal;
+pub mod range;
+pub mod replay;
+pub mod research;
+pub mod rules;
+pub mod save;
+pub mod status_effects;
 pub mod structures;
+pub mod tech;
+pub mod undo;
 pub mod units;
 
+use act::*;
+use research::*;
+use rules::*;
 use serde::{Deserialize, Serialize};
+use status_effects::*;
 use structures::*;
+use tech::*;
+use undo::*;
 use units::*;
 use weapon::*;
 
@@ -21,45 +39,70 @@ impl PartialEq for Tile {
     }
 }
 
-impl Tile {
-    pub fn distance_to(&self, other: &Tile) -> f32 {
-        let dx = self.x as f32 - other.x as f32;
-        let dy = self.y as f32 - other.y as f32;
-        return f32::sqrt(dx.powi(2) + dy.powi(2));
-    }
-}
-
 pub type UnitId = u32;
 pub type UnitHp = f32;
 pub type Team = u32;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Unit {
     pub id: UnitId,
     pub unit_type: UnitType,
     pub position: Tile,
     pub health: UnitHp,
+    pub shields: UnitHp,
+    pub fuel: f32,
     pub team: Team,
     pub has_moved: bool,
     pub has_attacked: bool,
+    pub status_effects: StatusEffects,
+    pub morph: Option<MorphInProgress>,
+    pub recently_damaged: bool, // Prevents shields from regenerating
+    pub cargo: Vec<Unit>,
+    pub experience: f32,
+    pub rank: Rank,
+}
+
+impl Unit {
+    pub fn new(id: UnitId, unit_type: UnitType, position: Tile, team: Team) -> Unit {
+        Unit {
+            id,
+            unit_type,
+            position,
+            health: unit_type.value().max_health,
+            shields: unit_type.value().max_shields,
+            fuel: unit_type.value().max_fuel,
+            team,
+            has_moved: false,
+            has_attacked: false,
+            status_effects: vec![],
+            morph: None,
+            recently_damaged: false,
+            cargo: vec![],
+            experience: 0.0,
+            rank: Rank::Rookie,
+        }
+    }
 }
 
 pub type StructureId = u32;
 pub type StructureHp = f32;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct Structure {
     pub id: StructureId,
     pub structure_type: StructureType,
     pub position: Tile,
     pub health: StructureHp,
     pub team: Team,
+    pub construction_turns_remaining: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub enum TerrainType {
     Grass,
     Water,
+    Mountain,
+    HighGround,
 }
 
 pub type ScenarioMap = Vec<Vec<TerrainType>>;
@@ -131,6 +174,50 @@ pub enum Command {
         attacker_id: UnitId,
         tile: Tile,
     },
+    Research {
+        structure_id: StructureId,
+        research: ResearchType,
+    },
+    Build {
+        structure_id: StructureId,
+        unit_type: UnitType,
+    },
+    Construct {
+        worker_id: UnitId,
+        structure_type: StructureType,
+        tile: Tile,
+    },
+    Morph {
+        unit_id: UnitId,
+        into: UnitType,
+    },
+    Load {
+        unit_id: UnitId,
+        transport: Transport,
+    },
+    Unload {
+        transport: Transport,
+        unit_id: UnitId,
+        tile: Tile,
+    },
+    Repair {
+        worker_id: UnitId,
+        target: RepairTarget,
+    },
+    Heal {
+        healer_id: UnitId,
+        target_id: UnitId,
+    },
+    // Ends the unit's turn without doing anything else.
+    Wait {
+        unit_id: UnitId,
+    },
+    // Move then act in one go, eg. move next to an enemy and attack it.
+    Act {
+        unit_id: UnitId,
+        path: Vec<Tile>,
+        action: ActAction,
+    },
     EndTurn,
 }
 
@@ -138,17 +225,42 @@ pub type TeamID = u32;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ScenarioState {
-    pub map: ScenarioMap,
+    pub map: Arc<ScenarioMap>, // Never changes during a game, so clones share it
     pub units: Vec<Unit>,
     pub structures: Vec<Structure>,
     pub active_team: TeamID,
     pub teams: Vec<TeamID>,
     pub creep: Creep,
+    pub team_states: HashMap<TeamID, TeamState>,
+    #[serde(default)]
+    pub rules: GameRules,
+    #[serde(default)]
+    pub turn: u32,
+    #[serde(default)]
+    pub rng: Rng,
+    #[serde(skip)]
+    pub undo_stack: UndoStack, // Only kept when the rules allow undo
 }
 
-pub type CreepMap = HashMap<TeamID, Vec<Vec<bool>>>;
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TeamState {
+    pub funds: u32,
+    pub upgrades: HashMap<ResearchType, u32>,
+    pub research_queue: Vec<ResearchInProgress>,
+    pub nydus_network: Vec<Unit>,
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResourceSummary {
+    pub funds: u32,
+    pub income: u32,
+    pub supply_used: u32,
+    pub supply_max: u32,
+}
+
+pub type CreepMap = HashMap<TeamID, Vec<Vec<bool>>>;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Creep(pub CreepMap);
 
 impl Creep {
@@ -168,47 +280,172 @@ impl Creep {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum CommandStatus {
     Ok,
     Partial,
     Err(CommandErr),
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum CommandErr {
     AlreadyMoved,
     AlreadyAttacked,
+    Incapacitated,
+    TargetNotDetected,
     NotImplemented,
     OutOfRange,
     WrongTeam,
+    InsufficientFunds,
+    ResearchUnavailable,
+    StructureBusy,
+    CannotProduce,
+    RequirementsNotMet,
+    TileOccupied,
+    InvalidPlacement,
+    RequiresCreep,
+    UnderConstruction,
+    Unpowered,
+    InvalidTarget,
+    CargoFull,
+    SupplyBlocked,
+    MissingAbility,
+    FullHealth,
+    NoLineOfSight,
+    CannotFireAfterMove,
+    PathBlocked,
+    InvalidPath,
     UnknownErr,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum CommandResult {
     Move {
         status: CommandStatus,
         tiles: Vec<Tile>,
-        // revealed: Vec<(Unit...)> etc.
+        trapped_by: Option<Unit>, // A hidden enemy which stopped the move
     },
     AttackGround {
         status: CommandStatus,
         unit_hp_changes: Vec<(UnitId, UnitHp)>,
+        unit_shield_changes: Vec<(UnitId, UnitHp)>,
+        rank_changes: Vec<(UnitId, Rank)>,
     },
     Attack {
         status: CommandStatus,
         unit_hp_changes: Vec<(UnitId, UnitHp)>,
+        unit_shield_changes: Vec<(UnitId, UnitHp)>,
+        rank_changes: Vec<(UnitId, Rank)>,
+    },
+    Research {
+        status: CommandStatus,
+        research: ResearchType,
+        level: u32,
+        funds: u32,
+    },
+    Build {
+        status: CommandStatus,
+        unit: Option<Unit>,
+        funds: u32,
+    },
+    Construct {
+        status: CommandStatus,
+        structure: Option<Structure>,
+        consumed_worker: Option<UnitId>,
+        funds: u32,
+    },
+    Morph {
+        status: CommandStatus,
+        unit_id: UnitId,
+        into: UnitType,
+        funds: u32,
+    },
+    Load {
+        status: CommandStatus,
+        unit_id: UnitId,
+        transport: Transport,
+    },
+    Unload {
+        status: CommandStatus,
+        unit: Option<Unit>,
+        transport: Transport,
+    },
+    Repair {
+        status: CommandStatus,
+        unit_hp_changes: Vec<(UnitId, UnitHp)>,
+        structure_hp_changes: Vec<(StructureId, StructureHp)>,
+        funds: u32,
+    },
+    Heal {
+        status: CommandStatus,
+        unit_hp_changes: Vec<(UnitId, UnitHp)>,
+    },
+    Wait {
+        status: CommandStatus,
+        unit_id: UnitId,
+    },
+    Act {
+        status: CommandStatus,
+        tiles: Vec<Tile>, // How far the unit moved
+        trapped_by: Option<Unit>,
+        action_result: Option<Box<CommandResult>>,
     },
     EndTurn {
         status: CommandStatus,
         new_active_team: Team,
+        completed_research: Vec<(ResearchType, u32)>,
+        completed_structures: Vec<StructureId>,
+        completed_morphs: Vec<(UnitId, UnitType)>,
+        crashed_units: Vec<UnitId>,
+        unit_hp_changes: Vec<(UnitId, UnitHp)>,
+        idle_units: Vec<UnitId>, // Units which could still act, see EndTurnPolicy
     },
 }
 
+impl CommandResult {
+    pub fn status(&self) -> CommandStatus {
+        match self {
+            CommandResult::Move { status, .. }
+            | CommandResult::AttackGround { status, .. }
+            | CommandResult::Attack { status, .. }
+            | CommandResult::Research { status, .. }
+            | CommandResult::Build { status, .. }
+            | CommandResult::Construct { status, .. }
+            | CommandResult::Morph { status, .. }
+            | CommandResult::Load { status, .. }
+            | CommandResult::Unload { status, .. }
+            | CommandResult::Repair { status, .. }
+            | CommandResult::Heal { status, .. }
+            | CommandResult::Wait { status, .. }
+            | CommandResult::Act { status, .. }
+            | CommandResult::EndTurn { status, .. } => *status,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AttackForecast {
+    pub damage_to_defender: Damage,
+    pub damage_to_attacker: Damage,
+    pub attacker_upgrades: Vec<(ResearchType, u32)>,
+    pub defender_upgrades: Vec<(ResearchType, u32)>,
+}
+
+const MINIMUM_DAMAGE: f32 = 0.5;
+
 // Mutating
 impl ScenarioState {
     pub fn execute(&mut self, command: Command) -> CommandResult {
+        if self.rules.undo == UndoPolicy::Never {
+            return self.apply(command);
+        }
+
+        let (result, delta) = self.execute_with_delta(command);
+        self.record(delta);
+        result
+    }
+
+    fn apply(&mut self, command: Command) -> CommandResult {
         match command {
             Command::Move { unit_id, tiles } => self.unit_move(unit_id, tiles),
             Command::Attack {
@@ -216,21 +453,51 @@ impl ScenarioState {
                 defender_id,
             } => self.attack(attacker_id, defender_id),
             Command::AttackGround { attacker_id, tile } => self.attack_ground(attacker_id, tile),
+            Command::Research {
+                structure_id,
+                research,
+            } => self.research(structure_id, research),
+            Command::Build {
+                structure_id,
+                unit_type,
+            } => self.build(structure_id, unit_type),
+            Command::Construct {
+                worker_id,
+                structure_type,
+                tile,
+            } => self.construct(worker_id, structure_type, tile),
+            Command::Morph { unit_id, into } => self.morph(unit_id, into),
+            Command::Load { unit_id, transport } => self.load(unit_id, transport),
+            Command::Unload {
+                transport,
+                unit_id,
+                tile,
+            } => self.unload(transport, unit_id, tile),
+            Command::Repair { worker_id, target } => self.repair(worker_id, target),
+            Command::Heal {
+                healer_id,
+                target_id,
+            } => self.heal(healer_id, target_id),
+            Command::Wait { unit_id } => self.wait(unit_id),
+            Command::Act {
+                unit_id,
+                path,
+                action,
+            } => self.act(unit_id, path, action),
             Command::EndTurn => self.end_turn(),
         }
     }
 
-    fn unit_move(&mut self, id: UnitId, tiles: Vec<Tile>) -> CommandResult {
-        let mut units_iterator = self.units.iter_mut();
-
-        let unit = units_iterator
-            .find(|u| u.id == id)
+    pub(crate) fn unit_move(&mut self, id: UnitId, tiles: Vec<Tile>) -> CommandResult {
+        let unit = self
+            .get_unit(id)
             .expect(format!("No unit found with id {}", id).as_str());
 
         if unit.has_moved {
             return CommandResult::Move {
                 status: CommandStatus::Err(CommandErr::AlreadyMoved),
                 tiles: vec![unit.position],
+                trapped_by: None,
             };
         }
 
@@ -238,22 +505,37 @@ impl ScenarioState {
             return CommandResult::Move {
                 status: CommandStatus::Err(CommandErr::WrongTeam),
                 tiles: vec![unit.position],
+                trapped_by: None,
+            };
+        }
+
+        if !unit.can_move() {
+            return CommandResult::Move {
+                status: CommandStatus::Err(CommandErr::Incapacitated),
+                tiles: vec![unit.position],
+                trapped_by: None,
+            };
+        }
+
+        if let Err(err) = self.check_path(id, &tiles) {
+            return CommandResult::Move {
+                status: CommandStatus::Err(err),
+                tiles: vec![unit.position],
+                trapped_by: None,
             };
         }
 
+        let layer = unit.layer();
         let mut successful_moves: Vec<Tile> = vec![];
         let mut pending_moves: Vec<Tile> = vec![];
         let mut status = CommandStatus::Err(CommandErr::UnknownErr);
+        let mut trapped_by: Option<Unit> = None;
 
         for Tile { x, y } in tiles {
-            // TODO - Check valid
-            //   Adjacent
-            //   Terrain/Fuel
-            //   Hasn't already moved
-
             // Check that each tile is free
-            let maybe_blocking_unit =
-                units_iterator.find(|u| u.position.x == x && u.position.y == y);
+            let maybe_blocking_unit = self.units.iter().find(|u| {
+                u.id != id && u.position.x == x && u.position.y == y && u.layer() == layer
+            });
 
             match maybe_blocking_unit {
                 None => {
@@ -265,15 +547,15 @@ impl ScenarioState {
                         pending_moves.push(Tile { x, y });
                         continue;
                     } else {
+                        // Running into an enemy that couldn't be seen springs a trap.
+                        if !self.is_visible_to(other_unit, unit.team) {
+                            trapped_by = Some(other_unit.clone());
+                        }
                         break;
                     }
                 }
             }
 
-            // Move the unit to there
-            unit.position.x = x;
-            unit.position.y = y;
-
             // Reduce movement according to terrain
             // Fuel etc.
 
@@ -281,16 +563,28 @@ impl ScenarioState {
             status = CommandStatus::Ok;
         }
 
+        // The unit stops on the last free tile it reached.
+        let unit = self.units.iter_mut().find(|u| u.id == id).unwrap();
+        if let Some(tile) = successful_moves.last() {
+            unit.position = *tile;
+        }
+
         // Set has_moved true
         unit.has_moved = true;
 
+        // Being trapped ends the unit's turn.
+        if trapped_by.is_some() {
+            unit.has_attacked = true;
+        }
+
         return CommandResult::Move {
             status,
             tiles: successful_moves,
+            trapped_by,
         };
     }
 
-    fn attack(&mut self, attacker_id: UnitId, defender_id: UnitId) -> CommandResult {
+    pub(crate) fn attack(&mut self, attacker_id: UnitId, defender_id: UnitId) -> CommandResult {
         // Validate attack
         //   Range
         //   Ammo
@@ -303,6 +597,8 @@ impl ScenarioState {
             return CommandResult::Attack {
                 status: CommandStatus::Err(CommandErr::WrongTeam),
                 unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
             };
         }
 
@@ -314,6 +610,29 @@ impl ScenarioState {
                     (attacker.id, attacker.health),
                     (defender.id, defender.health),
                 ],
+                unit_shield_changes: vec![
+                    (attacker.id, attacker.shields),
+                    (defender.id, defender.shields),
+                ],
+                rank_changes: vec![],
+            };
+        }
+
+        if !attacker.can_attack() {
+            return CommandResult::Attack {
+                status: CommandStatus::Err(CommandErr::Incapacitated),
+                unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
+            };
+        }
+
+        if !self.can_hit(attacker, defender) {
+            return CommandResult::Attack {
+                status: CommandStatus::Err(CommandErr::InvalidTarget),
+                unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
             };
         }
 
@@ -323,8 +642,27 @@ impl ScenarioState {
             return self.attack_ground(attacker_id, defender.position);
         }
 
+        if attacker.has_moved && !weapon.fire_after_move {
+            return CommandResult::Attack {
+                status: CommandStatus::Err(CommandErr::CannotFireAfterMove),
+                unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
+            };
+        }
+
+        // Burrowed units can still be caught by splash damage.
+        if defender.requires_detection() && !self.is_detected(defender, attacker.team) {
+            return CommandResult::Attack {
+                status: CommandStatus::Err(CommandErr::TargetNotDetected),
+                unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
+            };
+        }
+
         // Check Range
-        let in_range = check_range(attacker, defender);
+        let in_range = self.check_range(attacker, defender);
 
         if !in_range {
             return CommandResult::Attack {
@@ -333,27 +671,71 @@ impl ScenarioState {
                     (attacker.id, attacker.health),
                     (defender.id, defender.health),
                 ],
+                unit_shield_changes: vec![
+                    (attacker.id, attacker.shields),
+                    (defender.id, defender.shields),
+                ],
+                rank_changes: vec![],
+            };
+        }
+
+        if !self.has_sight_of(attacker, &defender.position, defender.layer()) {
+            return CommandResult::Attack {
+                status: CommandStatus::Err(CommandErr::NoLineOfSight),
+                unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
             };
         }
 
         // TODO Check Ammo
 
         // Calculate damage
-        let (attacker_damage, defender_damage) = self.calculate_damage(attacker.id, defender.id);
+        let (mut attacker_damage, mut defender_damage) =
+            self.calculate_damage(attacker.id, defender.id);
+        self.apply_luck(&mut defender_damage);
+        self.apply_luck(&mut attacker_damage);
 
         let (attacker, defender) = self.get_two_units_mut(attacker_id, defender_id).unwrap();
 
-        attacker.health -= attacker_damage;
-        defender.health -= defender_damage;
+        let attacker_was_alive = attacker.health > 0.0;
+        let defender_was_alive = defender.health > 0.0;
+        attacker.take_damage(&attacker_damage);
+        defender.take_damage(&defender_damage);
 
         attacker.has_attacked = true;
 
+        let mut rank_changes = vec![];
+        if attacker.health > 0.0 {
+            let experience = experience_for(
+                &defender_damage,
+                defender_was_alive && defender.health <= 0.0,
+            );
+            if let Some(rank) = attacker.gain_experience(experience) {
+                rank_changes.push((attacker.id, rank));
+            }
+        }
+        if defender.health > 0.0 {
+            let experience = experience_for(
+                &attacker_damage,
+                attacker_was_alive && attacker.health <= 0.0,
+            );
+            if let Some(rank) = defender.gain_experience(experience) {
+                rank_changes.push((defender.id, rank));
+            }
+        }
+
         let command_result = CommandResult::Attack {
             status: CommandStatus::Ok,
             unit_hp_changes: vec![
                 (attacker.id, attacker.health),
                 (defender.id, defender.health),
             ],
+            unit_shield_changes: vec![
+                (attacker.id, attacker.shields),
+                (defender.id, defender.shields),
+            ],
+            rank_changes,
         };
 
         self.units.retain(|unit| unit.health > 0.0);
@@ -361,25 +743,73 @@ impl ScenarioState {
         return command_result;
     }
 
-    fn attack_ground(&mut self, attacker_id: UnitId, tile: Tile) -> CommandResult {
+    pub(crate) fn attack_ground(&mut self, attacker_id: UnitId, tile: Tile) -> CommandResult {
         let attacker = self.get_unit(attacker_id).unwrap();
+        let attacker_team = attacker.team;
 
         if attacker.team != self.active_team {
-            return CommandResult::Attack {
+            return CommandResult::AttackGround {
                 status: CommandStatus::Err(CommandErr::WrongTeam),
                 unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
+            };
+        }
+
+        if attacker.has_attacked {
+            return CommandResult::AttackGround {
+                status: CommandStatus::Err(CommandErr::AlreadyAttacked),
+                unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
+            };
+        }
+
+        if !attacker.can_attack() {
+            return CommandResult::AttackGround {
+                status: CommandStatus::Err(CommandErr::Incapacitated),
+                unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
+            };
+        }
+
+        let Some(weapon) = attacker.unit_type.value().weapon_one else {
+            return CommandResult::AttackGround {
+                status: CommandStatus::Err(CommandErr::InvalidTarget),
+                unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
+            };
+        };
+
+        if attacker.has_moved && !weapon.fire_after_move {
+            return CommandResult::AttackGround {
+                status: CommandStatus::Err(CommandErr::CannotFireAfterMove),
+                unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
             };
         }
 
-        let weapon = attacker.unit_type.value().weapon_one.unwrap();
         match weapon.delivery {
             Delivery::Splash(splash) => {
-                let tile_in_range = check_range_to_tile(attacker, &tile);
-                println!("{:?}", tile_in_range);
+                let tile_in_range = self.check_range_to_tile(attacker, &tile);
                 if !tile_in_range {
                     return CommandResult::AttackGround {
                         status: CommandStatus::Err(CommandErr::OutOfRange),
                         unit_hp_changes: vec![],
+                        unit_shield_changes: vec![],
+                        rank_changes: vec![],
+                    };
+                }
+
+                if !self.has_sight_of(attacker, &tile, Layer::Ground) {
+                    return CommandResult::AttackGround {
+                        status: CommandStatus::Err(CommandErr::NoLineOfSight),
+                        unit_hp_changes: vec![],
+                        unit_shield_changes: vec![],
+                        rank_changes: vec![],
                     };
                 }
 
@@ -392,16 +822,38 @@ impl ScenarioState {
                         continue;
                     }
 
+                    if !weapon.can_target(unit.layer()) {
+                        continue;
+                    }
+
                     let damage = self.calculate_damage(attacker_id, unit.id).1;
                     damaged_units.insert(unit.id, damage);
                 }
 
+                // Roll in id order, so the same seed always gives the same damage.
+                let mut damaged_ids: Vec<UnitId> = damaged_units.keys().copied().collect();
+                damaged_ids.sort();
+                for unit_id in damaged_ids {
+                    let mut damage = damaged_units[&unit_id];
+                    self.apply_luck(&mut damage);
+                    damaged_units.insert(unit_id, damage);
+                }
+
                 let mut unit_hp_changes = vec![];
+                let mut unit_shield_changes = vec![];
+                let mut experience = 0.0;
                 for unit in self.units.iter_mut() {
                     match damaged_units.get(&unit.id) {
                         Some(damage) => {
-                            unit.health -= damage;
+                            let was_alive = unit.health > 0.0;
+                            unit.take_damage(damage);
                             unit_hp_changes.push((unit.id, unit.health));
+                            unit_shield_changes.push((unit.id, unit.shields));
+                            // No experience for friendly fire
+                            if unit.team != attacker_team {
+                                experience +=
+                                    experience_for(damage, was_alive && unit.health <= 0.0);
+                            }
                         }
                         None => continue,
                     }
@@ -409,34 +861,292 @@ impl ScenarioState {
 
                 let is_suicide = weapon.has_effect(&AdditionalEffect::Suicide);
 
-                if is_suicide {
-                    unit_hp_changes.push((attacker_id, 0.0));
+                let mut rank_changes = vec![];
+                if let Some(attacker) = self.units.iter_mut().find(|u| u.id == attacker_id) {
+                    attacker.has_attacked = true;
+                    if is_suicide {
+                        attacker.health = 0.0;
+                        unit_hp_changes.retain(|(id, _)| *id != attacker_id);
+                        unit_hp_changes.push((attacker_id, 0.0));
+                    } else if attacker.health > 0.0
+                        && let Some(rank) = attacker.gain_experience(experience)
+                    {
+                        rank_changes.push((attacker_id, rank));
+                    }
                 }
 
+                self.units.retain(|unit| unit.health > 0.0);
+
                 CommandResult::AttackGround {
                     status: CommandStatus::Ok,
                     unit_hp_changes,
+                    unit_shield_changes,
+                    rank_changes,
                 }
             }
             _ => CommandResult::AttackGround {
                 status: CommandStatus::Err(CommandErr::NotImplemented),
                 unit_hp_changes: vec![],
+                unit_shield_changes: vec![],
+                rank_changes: vec![],
             },
         }
     }
 
+    /// Returns false if there's no such unit.
+    pub fn apply_status_effect(&mut self, unit_id: UnitId, effect: StatusEffect) -> bool {
+        match self.units.iter_mut().find(|u| u.id == unit_id) {
+            Some(unit) => {
+                unit.apply_status_effect(effect);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn research(&mut self, structure_id: StructureId, research: ResearchType) -> CommandResult {
+        let structure = self
+            .get_structure(structure_id)
+            .expect("No structure found");
+        let team = structure.team;
+        let level = self.get_next_research_level(team, research);
+        let funds = self.team_states.get(&team).map_or(0, |t| t.funds);
+
+        let error = |err: CommandErr| CommandResult::Research {
+            status: CommandStatus::Err(err),
+            research,
+            level,
+            funds,
+        };
+
+        if team != self.active_team {
+            return error(CommandErr::WrongTeam);
+        }
+
+        if !structure.is_complete() {
+            return error(CommandErr::UnderConstruction);
+        }
+
+        if !self.is_powered(structure) {
+            return error(CommandErr::Unpowered);
+        }
+
+        let stats = research.value();
+        if structure.structure_type != stats.researched_at || level > stats.max_level {
+            return error(CommandErr::ResearchUnavailable);
+        }
+
+        let is_busy = self.team_states.get(&team).is_some_and(|t| {
+            t.research_queue
+                .iter()
+                .any(|in_progress| in_progress.structure_id == structure_id)
+        });
+        if is_busy {
+            return error(CommandErr::StructureBusy);
+        }
+
+        let cost = research.cost(level);
+        if funds < cost {
+            return error(CommandErr::InsufficientFunds);
+        }
+
+        let team_state = self.team_states.entry(team).or_default();
+        team_state.funds -= cost;
+        team_state.research_queue.push(ResearchInProgress {
+            research,
+            level,
+            structure_id,
+            turns_remaining: stats.turns,
+        });
+
+        CommandResult::Research {
+            status: CommandStatus::Ok,
+            research,
+            level,
+            funds: team_state.funds,
+        }
+    }
+
+    fn build(&mut self, structure_id: StructureId, unit_type: UnitType) -> CommandResult {
+        let structure = self
+            .get_structure(structure_id)
+            .expect("No structure found");
+        let team = structure.team;
+        let position = structure.position;
+        let funds = self.team_states.get(&team).map_or(0, |t| t.funds);
+
+        let error = |err: CommandErr| CommandResult::Build {
+            status: CommandStatus::Err(err),
+            unit: None,
+            funds,
+        };
+
+        if team != self.active_team {
+            return error(CommandErr::WrongTeam);
+        }
+
+        if !structure.is_complete() {
+            return error(CommandErr::UnderConstruction);
+        }
+
+        if !self.is_powered(structure) {
+            return error(CommandErr::Unpowered);
+        }
+
+        let stats = unit_type.value();
+        if stats.produced_at != Some(structure.structure_type) {
+            return error(CommandErr::CannotProduce);
+        }
+
+        if !self
+            .missing_requirements(team, &stats.requirements)
+            .is_empty()
+        {
+            return error(CommandErr::RequirementsNotMet);
+        }
+
+        if funds < stats.cost {
+            return error(CommandErr::InsufficientFunds);
+        }
+
+        if !self.has_supply_for(team, stats.supply_cost) {
+            return error(CommandErr::SupplyBlocked);
+        }
+
+        if self
+            .get_unit_at_layer(position.x, position.y, unit_type.layer())
+            .is_some()
+        {
+            return error(CommandErr::TileOccupied);
+        }
+
+        let mut unit = Unit::new(self.next_unit_id(), unit_type, position, team);
+        // Start with any extra health the team has researched.
+        unit.health = self.get_max_health(&unit);
+        // Newly built units can't act until their next turn.
+        unit.has_moved = true;
+        unit.has_attacked = true;
+        self.units.push(unit.clone());
+
+        let team_state = self.team_states.entry(team).or_default();
+        team_state.funds -= stats.cost;
+
+        CommandResult::Build {
+            status: CommandStatus::Ok,
+            unit: Some(unit),
+            funds: team_state.funds,
+        }
+    }
+
+    fn wait(&mut self, unit_id: UnitId) -> CommandResult {
+        let error = |err: CommandErr| CommandResult::Wait {
+            status: CommandStatus::Err(err),
+            unit_id,
+        };
+
+        let Some(unit) = self.units.iter_mut().find(|u| u.id == unit_id) else {
+            return error(CommandErr::InvalidTarget);
+        };
+
+        if unit.team != self.active_team {
+            return error(CommandErr::WrongTeam);
+        }
+
+        if unit.has_attacked {
+            return error(CommandErr::AlreadyAttacked);
+        }
+
+        unit.has_moved = true;
+        unit.has_attacked = true;
+
+        CommandResult::Wait {
+            status: CommandStatus::Ok,
+            unit_id,
+        }
+    }
+
     fn end_turn(&mut self) -> CommandResult {
-        println!("Ending turn");
+        let idle_units = match self.rules.end_turn_policy {
+            EndTurnPolicy::Allow => vec![],
+            EndTurnPolicy::Warn => self.units_pending_action(self.active_team),
+            EndTurnPolicy::AutoWait => {
+                let idle_units = self.units_pending_action(self.active_team);
+                for unit_id in idle_units.iter() {
+                    self.wait(*unit_id);
+                }
+                idle_units
+            }
+        };
+
+        for unit in self.units.iter_mut() {
+            if unit.team == self.active_team {
+                unit.tick_status_effects();
+            }
+        }
+        let completed_research = self.progress_research(self.active_team);
+        let completed_structures = self.progress_construction(self.active_team);
+        let completed_morphs = self.progress_morphs(self.active_team);
+
         let new_active_team = (self.active_team + 1) % (self.teams.len() as u32);
         self.active_team = new_active_team;
+        if self.teams.first() == Some(&new_active_team) {
+            self.turn += 1;
+        }
         for unit in self.units.iter_mut() {
             unit.has_attacked = false;
             unit.has_moved = false;
         }
+
+        self.regenerate_shields(new_active_team);
+        let unit_hp_changes = self.start_of_turn_healing(new_active_team);
+        let crashed_units = self.burn_fuel(new_active_team);
+
+        let income = self.get_income(new_active_team);
+        self.team_states.entry(new_active_team).or_default().funds += income;
+
         return CommandResult::EndTurn {
             status: CommandStatus::Ok,
             new_active_team,
+            completed_research,
+            completed_structures,
+            completed_morphs,
+            crashed_units,
+            unit_hp_changes,
+            idle_units,
+        };
+    }
+
+    fn progress_research(&mut self, team: TeamID) -> Vec<(ResearchType, u32)> {
+        let Some(team_state) = self.team_states.get_mut(&team) else {
+            return vec![];
         };
+
+        let mut completed = vec![];
+        for in_progress in team_state.research_queue.iter_mut() {
+            in_progress.turns_remaining = in_progress.turns_remaining.saturating_sub(1);
+            if in_progress.turns_remaining == 0 {
+                completed.push((in_progress.research, in_progress.level));
+            }
+        }
+        team_state
+            .research_queue
+            .retain(|in_progress| in_progress.turns_remaining > 0);
+
+        for (research, level) in completed.iter() {
+            team_state.upgrades.insert(*research, *level);
+        }
+
+        // Existing units gain any extra health straight away.
+        for (research, _) in completed.iter() {
+            let extra_health = research.value().modifiers.health;
+            for unit in self.units.iter_mut() {
+                if unit.team == team && research.applies_to(&unit.unit_type) {
+                    unit.health += extra_health;
+                }
+            }
+        }
+
+        completed
     }
 
     pub fn get_two_units_mut(
@@ -480,46 +1190,123 @@ impl ScenarioState {
     }
 }
 
-fn check_range(attacker: &Unit, defender: &Unit) -> bool {
-    let attacker_weapon = attacker
-        .unit_type
-        .value()
-        .weapon_one
-        .expect("No weapon found");
-
-    let (min, max) = match attacker_weapon.delivery {
-        Delivery::Melee => (1.0, 1.0),
-        Delivery::Ranged(min, max) => (min, max),
-        Delivery::Splash(splash) => splash.range,
-    };
-    let distance_between_units = attacker.position.distance_to(&defender.position);
-    let in_range = distance_between_units >= min && distance_between_units <= max;
-    in_range
-}
-
-fn check_range_to_tile(attacker: &Unit, tile: &Tile) -> bool {
-    let attacker_weapon = attacker
-        .unit_type
-        .value()
-        .weapon_one
-        .expect("No weapon found");
-
-    let (min, max) = match attacker_weapon.delivery {
-        Delivery::Melee => (1.0, 1.0),
-        Delivery::Ranged(min, max) => (min, max),
-        Delivery::Splash(splash) => splash.range,
-    };
-    let distance_to_tile = attacker.position.distance_to(tile);
-    let in_range = distance_to_tile >= min && distance_to_tile <= max;
-    in_range
-}
-
 // Non mutating
 impl ScenarioState {
+    pub fn check_range(&self, attacker: &Unit, defender: &Unit) -> bool {
+        self.check_range_to_tile(attacker, &defender.position)
+    }
+
+    pub fn check_range_to_tile(&self, attacker: &Unit, tile: &Tile) -> bool {
+        let attacker_weapon = attacker
+            .unit_type
+            .value()
+            .weapon_one
+            .expect("No weapon found");
+
+        let (min, max) = match attacker_weapon.delivery {
+            Delivery::Melee => (1.0, 1.0),
+            Delivery::Ranged(min, max) => (min, max),
+            Delivery::Splash(splash) => splash.range,
+        };
+        self.in_range(&attacker.position, tile, min, max)
+    }
+
     pub fn get_unit(&self, unit_id: UnitId) -> Option<&Unit> {
         self.units.iter().find(|u| u.id == unit_id)
     }
 
+    pub fn get_structure(&self, structure_id: StructureId) -> Option<&Structure> {
+        self.structures.iter().find(|s| s.id == structure_id)
+    }
+
+    /// An id no unit has, including those carried by transports or in a Nydus Network.
+    pub fn next_unit_id(&self) -> UnitId {
+        let carried = self.units.iter().flat_map(|u| &u.cargo);
+        let in_networks = self.team_states.values().flat_map(|t| &t.nydus_network);
+        self.units
+            .iter()
+            .chain(carried)
+            .chain(in_networks)
+            .map(|u| u.id)
+            .max()
+            .map_or(0, |id| id + 1)
+    }
+
+    pub fn get_income(&self, team: TeamID) -> u32 {
+        let owned_structures = self
+            .structures
+            .iter()
+            .filter(|s| s.team == team && s.is_complete())
+            .count();
+        owned_structures as u32 * self.rules.income_per_structure
+    }
+
+    pub fn resource_summary(&self, team: TeamID) -> ResourceSummary {
+        ResourceSummary {
+            funds: self.team_states.get(&team).map_or(0, |t| t.funds),
+            income: self.get_income(team),
+            supply_used: self.supply_used(team),
+            supply_max: self.supply_max(team),
+        }
+    }
+
+    pub fn get_next_research_level(&self, team: TeamID, research: ResearchType) -> u32 {
+        let Some(team_state) = self.team_states.get(&team) else {
+            return 1;
+        };
+        let researched = team_state.upgrades.get(&research).copied().unwrap_or(0);
+        let queued = team_state
+            .research_queue
+            .iter()
+            .filter(|in_progress| in_progress.research == research)
+            .count() as u32;
+        researched + queued + 1
+    }
+
+    /// Completed upgrades which affect this unit, and their levels.
+    pub fn get_upgrade_levels(&self, unit: &Unit) -> Vec<(ResearchType, u32)> {
+        let Some(team_state) = self.team_states.get(&unit.team) else {
+            return vec![];
+        };
+        let mut levels: Vec<(ResearchType, u32)> = team_state
+            .upgrades
+            .iter()
+            .filter(|(research, _)| research.applies_to(&unit.unit_type))
+            .map(|(research, level)| (*research, *level))
+            .collect();
+        levels.sort();
+        levels
+    }
+
+    pub fn get_upgrade_modifiers(&self, unit: &Unit) -> Modifiers {
+        self.get_upgrade_levels(unit)
+            .iter()
+            .fold(Modifiers::default(), |total, (research, level)| {
+                total.add(&research.value().modifiers.scaled(*level))
+            })
+    }
+
+    pub fn get_max_health(&self, unit: &Unit) -> f32 {
+        unit.unit_type.value().max_health + self.get_upgrade_modifiers(unit).health
+    }
+
+    pub fn get_armour(&self, unit: &Unit) -> f32 {
+        unit.unit_type.value().base_armour + self.get_upgrade_modifiers(unit).armour
+    }
+
+    pub fn forecast_attack(&self, attacker_id: UnitId, defender_id: UnitId) -> AttackForecast {
+        let (attacker, defender) = self.get_two_units(attacker_id, defender_id).unwrap();
+        let (damage_to_attacker, damage_to_defender) =
+            self.calculate_damage(attacker_id, defender_id);
+
+        AttackForecast {
+            damage_to_defender,
+            damage_to_attacker,
+            attacker_upgrades: self.get_upgrade_levels(attacker),
+            defender_upgrades: self.get_upgrade_levels(defender),
+        }
+    }
+
     pub fn get_moveable_tiles(&self, unit_id: UnitId) -> Vec<Tile> {
         let mut moveable_tiles = vec![];
         let unit = self.get_unit(unit_id).expect("No unit found!");
@@ -544,32 +1331,66 @@ impl ScenarioState {
         return moveable_tiles;
     }
 
-    pub fn calculate_damage(&self, attacker_id: UnitId, defender_id: UnitId) -> (f32, f32) {
+    pub fn calculate_damage(&self, attacker_id: UnitId, defender_id: UnitId) -> (Damage, Damage) {
         let (attacker, defender) = self.get_two_units(attacker_id, defender_id).unwrap();
-        let attack_damage = self.get_attack_damage(attacker, defender, attacker.health);
-        let new_defender_health = defender.health - attack_damage;
+        let attack_damage = if self.can_hit(attacker, defender) {
+            self.get_attack_damage(attacker, defender, attacker.health)
+        } else {
+            Damage::default()
+        };
+        let new_defender_health = defender.health - attack_damage.health;
 
-        let counter_attack_damage = if new_defender_health > 0.0 && check_range(defender, attacker)
+        let counter_allowed = match self.rules.counter_attacks {
+            CounterPolicy::Always => true,
+            CounterPolicy::Never => false,
+            CounterPolicy::AdjacentOnly => {
+                self.in_range(&attacker.position, &defender.position, 0.0, 1.0)
+            }
+        };
+
+        let counter_attack_damage = if new_defender_health > 0.0
+            && counter_allowed
+            && defender.can_counter()
+            && defender.can_attack()
+            && self.can_hit(defender, attacker)
+            && self.check_range(defender, attacker)
+            && self.has_sight_of(defender, &attacker.position, attacker.layer())
         {
             self.get_attack_damage(defender, attacker, new_defender_health)
         } else {
-            0.0
+            Damage::default()
         };
 
         return (counter_attack_damage, attack_damage);
     }
 
-    fn get_attack_damage(&self, attacker: &Unit, defender: &Unit, attacker_health: f32) -> f32 {
-        println!(
-            "{:?} attacking {:?} ",
-            attacker.unit_type, defender.unit_type
-        );
+    fn get_attack_damage(&self, attacker: &Unit, defender: &Unit, attacker_health: f32) -> Damage {
         let weapon = self.get_weapon(attacker);
-        let full_damage = self.calculate_full_damage(&weapon, &defender.unit_type);
-        let attacker_max_health = attacker.unit_type.value().max_health;
+        let full_damage = self.calculate_full_damage(&weapon, &defender.unit_type)
+            + self.get_upgrade_modifiers(attacker).damage;
+        let attacker_max_health = self.get_max_health(attacker);
         let weakness_scale = attacker_health / attacker_max_health;
-        let attack_damage = full_damage * weakness_scale;
-        attack_damage
+        let scale =
+            weakness_scale * attacker.damage_multiplier() * attacker.rank.value().damage_multiplier
+                / defender.rank.value().defence_multiplier;
+        if scale <= 0.0 {
+            return Damage::default();
+        }
+
+        // Shields ignore armour, so armour only reduces the damage left over once they break.
+        let shield_capacity = defender.shields / scale;
+        if full_damage <= shield_capacity {
+            return Damage {
+                shields: full_damage * scale,
+                health: 0.0,
+            };
+        }
+        let armoured_damage =
+            (full_damage - shield_capacity - self.get_armour(defender)).max(MINIMUM_DAMAGE);
+        Damage {
+            shields: defender.shields,
+            health: armoured_damage * scale,
+        }
     }
 
     pub fn get_weapon(&self, unit: &Unit) -> Weapon {
@@ -593,27 +1414,28 @@ impl ScenarioState {
     }
 
     // Will later require knowing which weapon is being used.
-    pub fn _get_targets_in_range(&self, attacker_id: UnitId) -> Vec<UnitId> {
-        let mut targets_in_range: Vec<UnitId> = vec![];
-        for unit in self.units.iter() {
-            if self._is_target_in_range(attacker_id, unit.id) {
-                targets_in_range.push(unit.id);
-            }
-        }
-        return targets_in_range;
-    }
-
-    pub fn _is_target_in_range(&self, _attacker_id: UnitId, _defender_id: UnitId) -> bool {
-        // TODO complete this function
-        true
+    pub fn get_targets_in_range(&self, attacker_id: UnitId) -> Vec<UnitId> {
+        let Some(attacker) = self.get_unit(attacker_id) else {
+            return vec![];
+        };
+        self.units
+            .iter()
+            .filter(|unit| unit.team != attacker.team)
+            .filter(|unit| self.target_status(attacker_id, unit.id) == TargetStatus::Hittable)
+            .map(|unit| unit.id)
+            .collect()
     }
 
     pub fn is_tile_moveable(&self, unit_id: UnitId, x: i32, y: i32) -> bool {
         match self.get_unit(unit_id) {
             Some(unit) => {
                 if let Ok((x, y)) = self.is_tile_within_bounds(x, y) {
+                    let terrain = self.map[y as usize][x as usize];
+                    if unit.layer() == Layer::Ground && matches!(terrain, TerrainType::Water) {
+                        return false;
+                    }
                     let move_through = self
-                        .get_unit_at(x, y)
+                        .get_unit_at_layer(x, y, unit.layer())
                         .map_or(true, |unit_2| unit_2.team == unit.team);
                     return move_through;
                 };
@@ -628,7 +1450,10 @@ impl ScenarioState {
     }
 
     pub fn is_tile_occupied(&self, unit_id: u32, x: u32, y: u32) -> bool {
-        if let Some(unit) = self.get_unit_at(x, y) {
+        let layer = self
+            .get_unit(unit_id)
+            .map_or(Layer::Ground, |unit| unit.layer());
+        if let Some(unit) = self.get_unit_at_layer(x, y, layer) {
             return unit.id != unit_id;
         }
         return false;
@@ -650,8 +1475,16 @@ impl ScenarioState {
         }
     }
 
-    pub fn get_movement_range(&self, _unit_id: &UnitId) -> u32 {
-        return 3;
+    pub fn get_movement_range(&self, unit_id: &UnitId) -> u32 {
+        let Some(unit) = self.get_unit(*unit_id) else {
+            return 0;
+        };
+        if !unit.can_move() {
+            return 0;
+        }
+        let upgrade_modifier = self.get_upgrade_modifiers(unit).movement;
+        (self.rules.movement_range as i32 + unit.movement_modifier() + upgrade_modifier).max(0)
+            as u32
     }
 
     pub fn get_possible_actions(&self, unit_id: &UnitId) -> Vec<UnitAction> {
@@ -664,9 +1497,16 @@ impl ScenarioState {
                     return actions;
                 }
 
-                actions.push(UnitAction::Attack);
+                let can_fire = unit
+                    .unit_type
+                    .value()
+                    .weapon_one
+                    .is_some_and(|weapon| !unit.has_moved || weapon.fire_after_move);
+                if unit.can_attack() && can_fire {
+                    actions.push(UnitAction::Attack);
+                }
 
-                if !unit.has_moved {
+                if !unit.has_moved && unit.can_move() {
                     actions.push(UnitAction::Move);
                 }
                 return actions;
@@ -675,14 +1515,49 @@ impl ScenarioState {
         }
     }
 
+    pub fn is_detected(&self, unit: &Unit, by_team: Team) -> bool {
+        let by_unit = self.units.iter().any(|detector| {
+            detector.team == by_team
+                && detector
+                    .unit_type
+                    .value()
+                    .detection_range
+                    .is_some_and(|range| self.distance(&detector.position, &unit.position) <= range)
+        });
+        let by_structure = self.structures.iter().any(|detector| {
+            detector.team == by_team
+                && detector.is_complete()
+                && detector
+                    .structure_type
+                    .value()
+                    .detection_range
+                    .is_some_and(|range| {
+                        detector
+                            .tiles()
+                            .iter()
+                            .any(|tile| self.distance(tile, &unit.position) <= range)
+                    })
+        });
+        by_unit || by_structure
+    }
+
     pub fn unit_cannot_act(&self, unit_id: &UnitId) -> bool {
         self.get_possible_actions(unit_id).len() == 0
     }
 
+    /// Units on the team which haven't finished their turn yet.
+    pub fn units_pending_action(&self, team: TeamID) -> Vec<UnitId> {
+        self.units
+            .iter()
+            .filter(|unit| unit.team == team && !self.unit_cannot_act(&unit.id))
+            .map(|unit| unit.id)
+            .collect()
+    }
+
     pub fn get_units_within_radius(&self, tile: Tile, radius: f32) -> Vec<&Unit> {
         let mut units: Vec<&Unit> = vec![];
         for unit in self.units.iter() {
-            if unit.position.distance_to(&tile) <= radius {
+            if self.distance(&unit.position, &tile) <= radius {
                 units.push(unit)
             }
         }
@@ -690,12 +1565,163 @@ impl ScenarioState {
     }
 
     pub fn _get_units_within_radius_mut(&mut self, tile: Tile, radius: f32) -> Vec<&mut Unit> {
+        let metric = self.rules.range_metric;
         let mut units: Vec<&mut Unit> = vec![];
         for unit in self.units.iter_mut() {
-            if unit.position.distance_to(&tile) <= radius {
+            if metric.distance(&unit.position, &tile) <= radius {
                 units.push(unit)
             }
         }
         return units;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dev_helpers::new_scenario_with_units;
+
+    fn state_with_units(units: &[(UnitType, (u32, u32), Team)]) -> ScenarioState {
+        new_scenario_with_units(GameRules::default(), units)
+    }
+
+    #[test]
+    fn attack_ground_only_fires_once_per_turn() {
+        let mut state = state_with_units(&[
+            (UnitType::SiegeTank, (0, 0), 0),
+            (UnitType::Roach, (3, 0), 1),
+        ]);
+        let command = Command::AttackGround {
+            attacker_id: 0,
+            tile: Tile { x: 3, y: 0 },
+        };
+
+        assert_eq!(state.execute(command.clone()).status(), CommandStatus::Ok);
+        assert!(state.get_unit(0).unwrap().has_attacked);
+
+        let health = state.get_unit(1).unwrap().health;
+        assert_eq!(
+            state.execute(command).status(),
+            CommandStatus::Err(CommandErr::AlreadyAttacked)
+        );
+        assert_eq!(state.get_unit(1).unwrap().health, health);
+    }
+
+    #[test]
+    fn attack_ground_removes_dead_units() {
+        let mut state = state_with_units(&[
+            (UnitType::SiegeTank, (0, 0), 0),
+            (UnitType::Marine, (3, 0), 1),
+        ]);
+        state.units[1].health = 1.0;
+
+        let result = state.execute(Command::AttackGround {
+            attacker_id: 0,
+            tile: Tile { x: 3, y: 0 },
+        });
+
+        assert_eq!(result.status(), CommandStatus::Ok);
+        assert!(state.get_unit(1).is_none());
+        assert!(state.units.iter().all(|unit| unit.health > 0.0));
+    }
+
+    #[test]
+    fn suicide_attackers_are_removed() {
+        let mut state = state_with_units(&[
+            (UnitType::Baneling, (4, 0), 1),
+            (UnitType::Roach, (5, 0), 0),
+        ]);
+        state.active_team = 1;
+
+        let result = state.execute(Command::AttackGround {
+            attacker_id: 0,
+            tile: Tile { x: 4, y: 0 },
+        });
+
+        assert_eq!(result.status(), CommandStatus::Ok);
+        assert!(state.get_unit(0).is_none());
+        assert!(state.get_unit(1).is_some());
+    }
+
+    #[test]
+    fn artillery_that_fired_is_not_pending() {
+        let mut state = state_with_units(&[
+            (UnitType::SiegeTank, (0, 0), 0),
+            (UnitType::Marine, (0, 5), 0),
+            (UnitType::Roach, (3, 0), 1),
+        ]);
+        state.execute(Command::AttackGround {
+            attacker_id: 0,
+            tile: Tile { x: 3, y: 0 },
+        });
+
+        assert_eq!(state.units_pending_action(0), vec![1]);
+    }
+
+    #[test]
+    fn auto_wait_only_reports_idle_units() {
+        let mut state = state_with_units(&[
+            (UnitType::SiegeTank, (0, 0), 0),
+            (UnitType::Marine, (0, 5), 0),
+            (UnitType::Roach, (3, 0), 1),
+        ]);
+        state.rules.end_turn_policy = EndTurnPolicy::AutoWait;
+        state.execute(Command::AttackGround {
+            attacker_id: 0,
+            tile: Tile { x: 3, y: 0 },
+        });
+
+        let CommandResult::EndTurn { idle_units, .. } = state.execute(Command::EndTurn) else {
+            panic!("EndTurn gave a different result");
+        };
+        assert_eq!(idle_units, vec![1]);
+    }
+
+    /// Out of range of the command centres, which would detect a burrowed roach.
+    fn move_marine_into_roach(burrowed: bool) -> (ScenarioState, CommandResult) {
+        let mut state =
+            state_with_units(&[(UnitType::Marine, (5, 0), 0), (UnitType::Roach, (7, 0), 1)]);
+        if burrowed {
+            state.apply_status_effect(1, StatusEffect::permanent(StatusEffectType::Burrowed));
+        }
+
+        let result = state.execute(Command::Move {
+            unit_id: 0,
+            tiles: vec![
+                Tile { x: 6, y: 0 },
+                Tile { x: 7, y: 0 },
+                Tile { x: 8, y: 0 },
+            ],
+        });
+        (state, result)
+    }
+
+    #[test]
+    fn hidden_enemies_spring_a_trap() {
+        let (state, result) = move_marine_into_roach(true);
+
+        let CommandResult::Move {
+            tiles, trapped_by, ..
+        } = result
+        else {
+            panic!("Move gave a different result");
+        };
+        assert_eq!(tiles, vec![Tile { x: 6, y: 0 }]);
+        assert_eq!(trapped_by.map(|unit| unit.id), Some(1));
+
+        let marine = state.get_unit(0).unwrap();
+        assert_eq!(marine.position, Tile { x: 6, y: 0 });
+        assert!(marine.has_attacked);
+    }
+
+    #[test]
+    fn visible_enemies_block_without_a_trap() {
+        let (state, result) = move_marine_into_roach(false);
+
+        let CommandResult::Move { trapped_by, .. } = result else {
+            panic!("Move gave a different result");
+        };
+        assert!(trapped_by.is_none());
+        assert!(!state.get_unit(0).unwrap().has_attacked);
+    }
+}
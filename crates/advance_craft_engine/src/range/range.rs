@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ScenarioMapMethods, ScenarioState, Tile};
+
+/// How distance between tiles is measured. Every range, radius and vision check uses the
+/// metric from the game rules, so a weapon's range means the same thing as a splash or detection radius.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RangeMetric {
+    Manhattan, // Advance Wars, diagonals are two tiles away
+    Chebyshev, // Diagonals are one tile away
+    #[default]
+    Euclidean,
+}
+
+impl RangeMetric {
+    pub fn distance(&self, a: &Tile, b: &Tile) -> f32 {
+        let dx = a.x.abs_diff(b.x) as f32;
+        let dy = a.y.abs_diff(b.y) as f32;
+        match self {
+            RangeMetric::Manhattan => dx + dy,
+            RangeMetric::Chebyshev => dx.max(dy),
+            RangeMetric::Euclidean => f32::sqrt(dx.powi(2) + dy.powi(2)),
+        }
+    }
+
+    pub fn in_range(&self, a: &Tile, b: &Tile, min: f32, max: f32) -> bool {
+        let distance = self.distance(a, b);
+        distance >= min && distance <= max
+    }
+}
+
+impl ScenarioState {
+    pub fn distance(&self, a: &Tile, b: &Tile) -> f32 {
+        self.rules.range_metric.distance(a, b)
+    }
+
+    pub fn in_range(&self, a: &Tile, b: &Tile, min: f32, max: f32) -> bool {
+        self.rules.range_metric.in_range(a, b, min, max)
+    }
+
+    /// Every tile on the map between `min` and `max` away from `tile`, inclusive.
+    pub fn tiles_in_range(&self, tile: Tile, min: f32, max: f32) -> impl Iterator<Item = Tile> {
+        let metric = self.rules.range_metric;
+        // No metric measures less than the distance along a single axis.
+        let reach = max.max(0.0).floor() as u32;
+        let max_x = (tile.x + reach).min(self.map.width().saturating_sub(1));
+        let max_y = (tile.y + reach).min(self.map.height().saturating_sub(1));
+        let min_x = tile.x.saturating_sub(reach);
+
+        (tile.y.saturating_sub(reach)..=max_y)
+            .flat_map(move |y| (min_x..=max_x).map(move |x| Tile { x, y }))
+            .filter(move |other| metric.in_range(&tile, other, min, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameRules, TargetStatus, UnitType, dev_helpers::new_scenario_with_units};
+
+    const CENTRE: Tile = Tile { x: 4, y: 3 };
+
+    fn state(range_metric: RangeMetric) -> ScenarioState {
+        let rules = GameRules {
+            range_metric,
+            ..Default::default()
+        };
+        new_scenario_with_units(
+            rules,
+            &[(UnitType::Roach, (4, 3), 1), (UnitType::Marine, (6, 4), 0)],
+        )
+    }
+
+    #[test]
+    fn metrics_measure_diagonals_differently() {
+        let diagonal = Tile { x: 5, y: 4 };
+        assert_eq!(RangeMetric::Manhattan.distance(&CENTRE, &diagonal), 2.0);
+        assert_eq!(RangeMetric::Chebyshev.distance(&CENTRE, &diagonal), 1.0);
+        assert_eq!(
+            RangeMetric::Euclidean.distance(&CENTRE, &diagonal),
+            2f32.sqrt()
+        );
+    }
+
+    #[test]
+    fn tiles_in_range_follow_the_metric() {
+        let count = |metric, tile, min, max| state(metric).tiles_in_range(tile, min, max).count();
+
+        assert_eq!(count(RangeMetric::Manhattan, CENTRE, 0.0, 1.0), 5);
+        assert_eq!(count(RangeMetric::Chebyshev, CENTRE, 0.0, 1.0), 9);
+        assert_eq!(count(RangeMetric::Euclidean, CENTRE, 0.0, 1.5), 9);
+        assert_eq!(count(RangeMetric::Manhattan, CENTRE, 1.0, 1.0), 4);
+        assert_eq!(
+            count(RangeMetric::Manhattan, Tile { x: 0, y: 0 }, 0.0, 1.0),
+            3
+        );
+    }
+
+    #[test]
+    fn weapon_ranges_use_the_rules_metric() {
+        assert_eq!(
+            state(RangeMetric::Euclidean).target_status(0, 1),
+            TargetStatus::OutOfRange
+        );
+        assert_eq!(
+            state(RangeMetric::Chebyshev).target_status(0, 1),
+            TargetStatus::Hittable
+        );
+    }
+}
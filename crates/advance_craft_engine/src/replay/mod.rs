@@ -0,0 +1,3 @@
+pub mod replay;
+
+pub use replay::*;
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Command, CommandResult, ScenarioState};
+
+/// Bumped whenever the replay format changes.
+pub const REPLAY_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub command: Command,
+    pub result: CommandResult, // What the recording engine made of the command
+}
+
+/// A recorded game: the state it started from, which includes its rules and random seed, and
+/// every command issued in order. Engine commands are deterministic, so replaying them
+/// reproduces the game exactly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub engine_version: String,
+    pub initial_state: ScenarioState,
+    pub steps: Vec<ReplayStep>,
+    #[serde(skip)]
+    playback: Option<(usize, ScenarioState)>, // How many steps have been played, and the result
+    #[serde(skip)]
+    undo_points: Vec<usize>, // The step for each entry in the recorded state's undo stack
+}
+
+/// Where a replay stopped matching the engine playing it back.
+#[derive(Debug, Clone)]
+pub struct ReplayMismatch {
+    pub step: usize,
+    pub expected: Box<CommandResult>,
+    pub actual: Box<CommandResult>,
+}
+
+impl Replay {
+    pub fn new(initial_state: &ScenarioState) -> Replay {
+        let mut initial_state = initial_state.clone();
+        initial_state.undo_stack.clear();
+
+        Replay {
+            version: REPLAY_VERSION,
+            engine_version: env!("CARGO_PKG_VERSION").to_string(),
+            initial_state,
+            steps: vec![],
+            playback: None,
+            undo_points: vec![],
+        }
+    }
+
+    pub fn record(&mut self, command: Command, result: CommandResult) {
+        self.steps.push(ReplayStep { command, result });
+    }
+
+    /// Records a command which was just executed on `state`, keeping track of which steps its
+    /// undo stack can take back. Failed commands and commands which changed nothing never
+    /// reach the undo stack, so the stack and the steps can't simply be popped together.
+    pub fn record_played(
+        &mut self,
+        command: Command,
+        result: CommandResult,
+        state: &ScenarioState,
+    ) {
+        let step = self.steps.len();
+        self.record(command, result);
+        if state.undo_stack.len() > self.undo_points.len() {
+            self.undo_points.push(step);
+        } else {
+            self.undo_points.truncate(state.undo_stack.len());
+        }
+    }
+
+    /// Drops the steps taken back by `ScenarioState::undo`: the undone command and anything
+    /// recorded after it. Returns whether there was anything to drop.
+    pub fn undo(&mut self) -> bool {
+        match self.undo_points.pop() {
+            Some(step) => {
+                self.steps.truncate(step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// How many steps have been played back.
+    pub fn position(&self) -> usize {
+        self.playback.as_ref().map_or(0, |(position, _)| *position)
+    }
+
+    /// The state after the steps played back so far.
+    pub fn state(&self) -> &ScenarioState {
+        self.playback
+            .as_ref()
+            .map_or(&self.initial_state, |(_, state)| state)
+    }
+
+    /// Plays the next step, returning its result, or None at the end of the replay.
+    pub fn step(&mut self) -> Option<CommandResult> {
+        let position = self.position();
+        let command = self.steps.get(position)?.command.clone();
+
+        let (position, state) = self
+            .playback
+            .get_or_insert_with(|| (0, self.initial_state.clone()));
+        *position += 1;
+        Some(state.execute(command))
+    }
+
+    /// Moves playback to just after the given number of steps. Going backwards means playing
+    /// forward again from the start.
+    pub fn seek(&mut self, position: usize) {
+        if position < self.position() {
+            self.playback = None;
+        }
+        while self.position() < position.min(self.steps.len()) {
+            self.step();
+        }
+    }
+
+    /// Plays the whole replay on a fresh copy of the initial state, checking every command
+    /// still gives the result it was recorded with.
+    pub fn verify(&self) -> Result<(), ReplayMismatch> {
+        let mut state = self.initial_state.clone();
+        for (index, step) in self.steps.iter().enumerate() {
+            let actual = state.execute(step.command.clone());
+            if actual != step.result {
+                return Err(ReplayMismatch {
+                    step: index,
+                    expected: Box::new(step.result.clone()),
+                    actual: Box::new(actual),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CommandStatus, GameRules, Tile,
+        dev_helpers::new_scenario_state,
+        rules::{Rng, UndoPolicy},
+    };
+
+    /// A replay of random legal commands in a game with luck, along with the final state.
+    fn recorded_game(steps: usize) -> (Replay, ScenarioState) {
+        let rules = GameRules {
+            luck: 0.2,
+            seed: 11,
+            ..Default::default()
+        };
+        let mut state = new_scenario_state(rules);
+        let mut replay = Replay::new(&state);
+        let mut picker = Rng::new(5);
+
+        for _ in 0..steps {
+            let commands = state.legal_commands(state.active_team);
+            let command = commands[picker.next_u64() as usize % commands.len()].clone();
+            let result = state.execute(command.clone());
+            replay.record(command, result);
+        }
+        (replay, state)
+    }
+
+    #[test]
+    fn replays_reproduce_the_game() {
+        let (replay, state) = recorded_game(100);
+        assert!(replay.verify().is_ok());
+
+        let json = serde_json::to_string(&replay).unwrap();
+        let mut loaded: Replay = serde_json::from_str(&json).unwrap();
+        assert!(loaded.verify().is_ok());
+
+        loaded.seek(loaded.steps.len());
+        assert_eq!(loaded.state().state_hash(), state.state_hash());
+    }
+
+    #[test]
+    fn seeking_backwards_replays_from_the_start() {
+        let (mut replay, _) = recorded_game(40);
+        replay.seek(25);
+        let hash = replay.state().state_hash();
+
+        replay.seek(replay.steps.len());
+        replay.seek(10);
+        assert_eq!(replay.position(), 10);
+        replay.seek(25);
+        assert_eq!(replay.state().state_hash(), hash);
+
+        replay.seek(1000);
+        assert_eq!(replay.position(), replay.steps.len());
+        assert!(replay.step().is_none());
+    }
+
+    #[test]
+    fn tampered_replays_are_caught() {
+        let (mut replay, _) = recorded_game(40);
+        replay.steps[20].result = replay.steps[19].result.clone();
+
+        let mismatch = replay.verify().unwrap_err();
+        assert_eq!(mismatch.step, 20);
+    }
+
+    fn play(state: &mut ScenarioState, replay: &mut Replay, command: Command) {
+        let result = state.execute(command.clone());
+        replay.record_played(command, result, state);
+    }
+
+    #[test]
+    fn undoing_drops_the_undone_steps() {
+        let rules = GameRules {
+            undo: UndoPolicy::Always,
+            ..Default::default()
+        };
+        let mut state = new_scenario_state(rules);
+        let mut replay = Replay::new(&state);
+        play(&mut state, &mut replay, Command::EndTurn);
+        play(&mut state, &mut replay, Command::EndTurn);
+        let steps = replay.steps.len();
+        let hash = state.state_hash();
+
+        play(&mut state, &mut replay, Command::Wait { unit_id: 0 });
+        // Failed commands never reach the undo stack, but go once what came before them does.
+        let step = Tile { x: 2, y: 2 };
+        let result = state.execute(Command::Move {
+            unit_id: 0,
+            tiles: vec![step],
+        });
+        assert!(matches!(result.status(), CommandStatus::Err(_)));
+        replay.record_played(
+            Command::Move {
+                unit_id: 0,
+                tiles: vec![step],
+            },
+            result,
+            &state,
+        );
+
+        assert!(state.undo());
+        assert!(replay.undo());
+        assert_eq!(replay.steps.len(), steps);
+        assert!(!state.undo());
+        assert!(!replay.undo());
+
+        assert!(replay.verify().is_ok());
+        replay.seek(replay.steps.len());
+        assert_eq!(replay.state().state_hash(), hash);
+        assert_eq!(state.state_hash(), hash);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod research;
+
+pub use research::*;
@@ -0,0 +1,330 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{StructureId, StructureType, UnitType, units::Race};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ResearchType {
+    InfantryWeapons,
+    InfantryArmour,
+    CombatShield,
+    MeleeAttacks,
+    MissileAttacks,
+    GroundCarapace,
+    MetabolicBoost,
+}
+
+/// Modifiers are granted once per level researched.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Modifiers {
+    pub damage: f32,
+    pub armour: f32,
+    pub health: f32,
+    pub movement: i32,
+}
+
+impl Modifiers {
+    pub fn scaled(&self, level: u32) -> Modifiers {
+        Modifiers {
+            damage: self.damage * level as f32,
+            armour: self.armour * level as f32,
+            health: self.health * level as f32,
+            movement: self.movement * level as i32,
+        }
+    }
+
+    pub fn add(&self, other: &Modifiers) -> Modifiers {
+        Modifiers {
+            damage: self.damage + other.damage,
+            armour: self.armour + other.armour,
+            health: self.health + other.health,
+            movement: self.movement + other.movement,
+        }
+    }
+}
+
+pub struct ResearchStats {
+    pub name: &'static str,
+    pub race: Race,
+    pub max_level: u32,
+    pub cost: u32, // Per level
+    pub turns: u32,
+    pub researched_at: StructureType,
+    pub applies_to: [Option<UnitType>; 4],
+    pub modifiers: Modifiers,
+}
+
+impl ResearchType {
+    pub const ALL: [ResearchType; 7] = [
+        ResearchType::InfantryWeapons,
+        ResearchType::InfantryArmour,
+        ResearchType::CombatShield,
+        ResearchType::MeleeAttacks,
+        ResearchType::MissileAttacks,
+        ResearchType::GroundCarapace,
+        ResearchType::MetabolicBoost,
+    ];
+
+    pub const fn value(&self) -> ResearchStats {
+        use ResearchType::*;
+        match *self {
+            InfantryWeapons => INFANTRY_WEAPONS,
+            InfantryArmour => INFANTRY_ARMOUR,
+            CombatShield => COMBAT_SHIELD,
+            MeleeAttacks => MELEE_ATTACKS,
+            MissileAttacks => MISSILE_ATTACKS,
+            GroundCarapace => GROUND_CARAPACE,
+            MetabolicBoost => METABOLIC_BOOST,
+        }
+    }
+
+    pub fn applies_to(&self, unit_type: &UnitType) -> bool {
+        self.value().applies_to.contains(&Some(*unit_type))
+    }
+
+    pub fn cost(&self, level: u32) -> u32 {
+        self.value().cost * level
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResearchInProgress {
+    pub research: ResearchType,
+    pub level: u32,
+    pub structure_id: StructureId,
+    pub turns_remaining: u32,
+}
+
+use Race::*;
+use UnitType::*;
+
+pub const INFANTRY_WEAPONS: ResearchStats = ResearchStats {
+    name: "Infantry Weapons",
+    race: Terran,
+    max_level: 3,
+    cost: 100,
+    turns: 3,
+    researched_at: StructureType::EngineeringBay,
+    applies_to: [Some(Marine), None, None, None],
+    modifiers: Modifiers {
+        damage: 1.0,
+        armour: 0.0,
+        health: 0.0,
+        movement: 0,
+    },
+};
+
+pub const INFANTRY_ARMOUR: ResearchStats = ResearchStats {
+    name: "Infantry Armour",
+    race: Terran,
+    max_level: 3,
+    cost: 100,
+    turns: 3,
+    researched_at: StructureType::EngineeringBay,
+    applies_to: [Some(Marine), None, None, None],
+    modifiers: Modifiers {
+        damage: 0.0,
+        armour: 1.0,
+        health: 0.0,
+        movement: 0,
+    },
+};
+
+pub const COMBAT_SHIELD: ResearchStats = ResearchStats {
+    name: "Combat Shield",
+    race: Terran,
+    max_level: 1,
+    cost: 100,
+    turns: 2,
+    researched_at: StructureType::Barracks,
+    applies_to: [Some(Marine), None, None, None],
+    modifiers: Modifiers {
+        damage: 0.0,
+        armour: 0.0,
+        health: 10.0,
+        movement: 0,
+    },
+};
+
+pub const MELEE_ATTACKS: ResearchStats = ResearchStats {
+    name: "Melee Attacks",
+    race: Zerg,
+    max_level: 3,
+    cost: 100,
+    turns: 3,
+    researched_at: StructureType::EvolutionChamber,
+    applies_to: [Some(Zergling), Some(Baneling), None, None],
+    modifiers: Modifiers {
+        damage: 1.0,
+        armour: 0.0,
+        health: 0.0,
+        movement: 0,
+    },
+};
+
+pub const MISSILE_ATTACKS: ResearchStats = ResearchStats {
+    name: "Missile Attacks",
+    race: Zerg,
+    max_level: 3,
+    cost: 100,
+    turns: 3,
+    researched_at: StructureType::EvolutionChamber,
+    applies_to: [Some(Roach), Some(Ravager), None, None],
+    modifiers: Modifiers {
+        damage: 2.0,
+        armour: 0.0,
+        health: 0.0,
+        movement: 0,
+    },
+};
+
+pub const GROUND_CARAPACE: ResearchStats = ResearchStats {
+    name: "Ground Carapace",
+    race: Zerg,
+    max_level: 3,
+    cost: 150,
+    turns: 3,
+    researched_at: StructureType::EvolutionChamber,
+    applies_to: [Some(Zergling), Some(Baneling), Some(Roach), Some(Ravager)],
+    modifiers: Modifiers {
+        damage: 0.0,
+        armour: 1.0,
+        health: 0.0,
+        movement: 0,
+    },
+};
+
+pub const METABOLIC_BOOST: ResearchStats = ResearchStats {
+    name: "Metabolic Boost",
+    race: Zerg,
+    max_level: 1,
+    cost: 100,
+    turns: 2,
+    researched_at: StructureType::SpawningPool,
+    applies_to: [Some(Zergling), None, None, None],
+    modifiers: Modifiers {
+        damage: 0.0,
+        armour: 0.0,
+        health: 0.0,
+        movement: 1,
+    },
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Command, CommandErr, CommandResult, CommandStatus, GameRules, ScenarioState, Structure,
+        Tile, dev_helpers::new_scenario_with_units,
+    };
+    use ResearchType::*;
+
+    /// A marine and an engineering bay against a roach.
+    fn state() -> ScenarioState {
+        let mut state = new_scenario_with_units(
+            GameRules::default(),
+            &[(Marine, (2, 0), 0), (Roach, (3, 0), 1)],
+        );
+        state.structures.push(Structure::new(
+            10,
+            StructureType::EngineeringBay,
+            Tile { x: 0, y: 0 },
+            0,
+        ));
+        state
+    }
+
+    fn research(state: &mut ScenarioState, research: ResearchType) -> CommandStatus {
+        state
+            .execute(Command::Research {
+                structure_id: 10,
+                research,
+            })
+            .status()
+    }
+
+    fn end_rounds(state: &mut ScenarioState, rounds: u32) {
+        for _ in 0..rounds * state.teams.len() as u32 {
+            state.execute(Command::EndTurn);
+        }
+    }
+
+    #[test]
+    fn research_costs_funds_and_finishes_after_its_turns() {
+        let mut state = state();
+        let funds = state.team_states[&0].funds;
+        let damage = state.calculate_damage(0, 1).1.total();
+
+        assert_eq!(research(&mut state, InfantryWeapons), CommandStatus::Ok);
+        assert_eq!(state.team_states[&0].funds, funds - INFANTRY_WEAPONS.cost);
+
+        end_rounds(&mut state, INFANTRY_WEAPONS.turns - 1);
+        assert!(state.team_states[&0].upgrades.is_empty());
+        end_rounds(&mut state, 1);
+        assert_eq!(state.team_states[&0].upgrades[&InfantryWeapons], 1);
+        assert!(state.calculate_damage(0, 1).1.total() > damage);
+    }
+
+    #[test]
+    fn structures_research_one_thing_at_a_time() {
+        let mut state = state();
+        assert_eq!(research(&mut state, InfantryWeapons), CommandStatus::Ok);
+        assert_eq!(
+            research(&mut state, InfantryArmour),
+            CommandStatus::Err(CommandErr::StructureBusy)
+        );
+    }
+
+    #[test]
+    fn research_happens_at_the_right_structure() {
+        let mut state = state();
+        assert_eq!(
+            research(&mut state, CombatShield),
+            CommandStatus::Err(CommandErr::ResearchUnavailable)
+        );
+    }
+
+    #[test]
+    fn units_built_after_research_get_the_extra_health() {
+        let mut state = state();
+        let barracks = Structure::new(11, StructureType::Barracks, Tile { x: 6, y: 2 }, 0);
+        state.structures.push(barracks);
+        let status = state
+            .execute(Command::Research {
+                structure_id: 11,
+                research: CombatShield,
+            })
+            .status();
+        assert_eq!(status, CommandStatus::Ok);
+        end_rounds(&mut state, COMBAT_SHIELD.turns);
+
+        let result = state.execute(Command::Build {
+            structure_id: 11,
+            unit_type: Marine,
+        });
+        let CommandResult::Build {
+            unit: Some(marine), ..
+        } = result
+        else {
+            panic!("The marine wasn't built");
+        };
+        assert_eq!(
+            marine.health,
+            Marine.value().max_health + COMBAT_SHIELD.modifiers.health
+        );
+        assert_eq!(marine.health, state.get_max_health(&marine));
+    }
+
+    #[test]
+    fn upgrades_only_apply_to_the_researching_team() {
+        let mut state = state();
+        let armour = state.get_armour(state.get_unit(0).unwrap());
+
+        let upgrades = &mut state.team_states.get_mut(&1).unwrap().upgrades;
+        upgrades.insert(InfantryArmour, 2);
+        assert_eq!(state.get_armour(state.get_unit(0).unwrap()), armour);
+
+        let upgrades = &mut state.team_states.get_mut(&0).unwrap().upgrades;
+        upgrades.insert(InfantryArmour, 2);
+        assert_eq!(state.get_armour(state.get_unit(0).unwrap()), armour + 2.0);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod rules;
+
+pub use rules::*;
@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ScenarioState, TeamID, range::RangeMetric, units::Damage};
+
+/// Everything that can be tweaked about how a game is played, chosen when the game is created.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameRules {
+    pub fog: bool,
+    pub starting_funds: u32,
+    pub income_per_structure: u32,
+    pub movement_range: u32,
+    pub turn_limit: Option<u32>, // In rounds, where every team has had a turn
+    pub luck: f32,               // Up to this fraction of extra damage is added at random
+    pub seed: u64,
+    pub counter_attacks: CounterPolicy,
+    pub end_turn_policy: EndTurnPolicy,
+    pub range_metric: RangeMetric,
+    pub win_conditions: Vec<WinCondition>,
+    pub undo: UndoPolicy,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules {
+            fog: false,
+            starting_funds: 500,
+            income_per_structure: 100,
+            movement_range: 3,
+            turn_limit: None,
+            luck: 0.0,
+            seed: 0,
+            counter_attacks: CounterPolicy::Always,
+            end_turn_policy: EndTurnPolicy::Allow,
+            range_metric: RangeMetric::Euclidean,
+            win_conditions: vec![WinCondition::Annihilation],
+            undo: UndoPolicy::Never,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CounterPolicy {
+    Always,
+    Never,
+    AdjacentOnly, // Only units attacked from next door fight back
+}
+
+/// What happens to units which could still act when their team ends its turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EndTurnPolicy {
+    Allow,
+    Warn,     // The turn ends, but the idle units are reported
+    AutoWait, // The idle units wait, then the turn ends
+}
+
+/// When a player may take back commands made earlier in their turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UndoPolicy {
+    Never,
+    BeforeReveal, // Until a command shows something new, eg. a hidden unit or a luck roll
+    Always,       // Any command this turn, eg. for friendly games on one screen
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WinCondition {
+    Annihilation,      // A team with no units or structures left loses
+    DestroyStructures, // A team with no structures left loses
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameOutcome {
+    Ongoing,
+    Winner(TeamID),
+    Draw,
+}
+
+/// A small deterministic random number generator (SplitMix64), so that games with luck can be
+/// replayed exactly from their seed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in the range [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+impl ScenarioState {
+    /// Adds a random amount of extra damage when luck is on. Only damage which gets through
+    /// shields is affected.
+    pub(crate) fn apply_luck(&mut self, damage: &mut Damage) {
+        if self.rules.luck <= 0.0 || damage.health <= 0.0 {
+            return;
+        }
+        damage.health *= 1.0 + self.rng.next_f32() * self.rules.luck;
+    }
+
+    fn is_eliminated(&self, team: TeamID) -> bool {
+        let has_units = self.units.iter().any(|unit| unit.team == team);
+        let has_structures = self
+            .structures
+            .iter()
+            .any(|structure| structure.team == team);
+
+        self.rules
+            .win_conditions
+            .iter()
+            .any(|condition| match condition {
+                WinCondition::Annihilation => !has_units && !has_structures,
+                WinCondition::DestroyStructures => !has_structures,
+            })
+    }
+
+    pub fn outcome(&self) -> GameOutcome {
+        let remaining: Vec<TeamID> = self
+            .teams
+            .iter()
+            .copied()
+            .filter(|team| !self.is_eliminated(*team))
+            .collect();
+
+        match remaining.as_slice() {
+            [] => return GameOutcome::Draw,
+            [winner] => return GameOutcome::Winner(*winner),
+            _ => {}
+        }
+
+        // At the turn limit, whoever holds the most structures wins.
+        if self.rules.turn_limit.is_some_and(|limit| self.turn > limit) {
+            let structure_count = |team: &TeamID| {
+                self.structures
+                    .iter()
+                    .filter(|structure| structure.team == *team)
+                    .count()
+            };
+            let most = remaining.iter().map(structure_count).max().unwrap_or(0);
+            let leaders: Vec<&TeamID> = remaining
+                .iter()
+                .filter(|team| structure_count(team) == most)
+                .collect();
+            return match leaders.as_slice() {
+                [winner] => GameOutcome::Winner(**winner),
+                _ => GameOutcome::Draw,
+            };
+        }
+
+        GameOutcome::Ongoing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{UnitType, dev_helpers::new_scenario_with_units};
+
+    fn rules(counter_attacks: CounterPolicy) -> GameRules {
+        GameRules {
+            counter_attacks,
+            ..Default::default()
+        }
+    }
+
+    /// Damage a roach takes back when attacking another roach `distance` tiles away.
+    fn counter_damage(counter_attacks: CounterPolicy, distance: u32) -> f32 {
+        let state = new_scenario_with_units(
+            rules(counter_attacks),
+            &[
+                (UnitType::Roach, (4, 3), 1),
+                (UnitType::Roach, (4, 3 - distance), 0),
+            ],
+        );
+        state.calculate_damage(0, 1).0.total()
+    }
+
+    #[test]
+    fn missing_rules_fall_back_to_the_defaults() {
+        let rules: GameRules = serde_json::from_str(r#"{"fog": true}"#).unwrap();
+        assert_eq!(
+            rules,
+            GameRules {
+                fog: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn counter_attacks_follow_the_policy() {
+        assert!(counter_damage(CounterPolicy::Always, 2) > 0.0);
+        assert_eq!(counter_damage(CounterPolicy::Never, 1), 0.0);
+        assert!(counter_damage(CounterPolicy::AdjacentOnly, 1) > 0.0);
+        assert_eq!(counter_damage(CounterPolicy::AdjacentOnly, 2), 0.0);
+    }
+
+    #[test]
+    fn luck_is_bounded_and_repeatable() {
+        let mut state = new_scenario_with_units(
+            GameRules {
+                luck: 0.5,
+                seed: 7,
+                ..Default::default()
+            },
+            &[],
+        );
+        let mut replayed = state.clone();
+
+        for _ in 0..20 {
+            let mut damage = Damage {
+                shields: 4.0,
+                health: 10.0,
+            };
+            state.apply_luck(&mut damage);
+            assert_eq!(damage.shields, 4.0);
+            assert!((10.0..15.0).contains(&damage.health));
+
+            let mut replayed_damage = Damage {
+                shields: 4.0,
+                health: 10.0,
+            };
+            replayed.apply_luck(&mut replayed_damage);
+            assert_eq!(damage, replayed_damage);
+        }
+    }
+
+    #[test]
+    fn teams_lose_by_the_win_conditions() {
+        let mut state =
+            new_scenario_with_units(GameRules::default(), &[(UnitType::Roach, (4, 3), 1)]);
+        state.structures.retain(|structure| structure.team == 0);
+        assert_eq!(state.outcome(), GameOutcome::Ongoing);
+
+        state.rules.win_conditions = vec![WinCondition::DestroyStructures];
+        assert_eq!(state.outcome(), GameOutcome::Winner(0));
+    }
+
+    #[test]
+    fn most_structures_win_at_the_turn_limit() {
+        let mut state = new_scenario_with_units(
+            GameRules {
+                turn_limit: Some(10),
+                ..Default::default()
+            },
+            &[],
+        );
+        state.turn = 11;
+        assert_eq!(state.outcome(), GameOutcome::Draw);
+
+        let hatchery = state.structures.iter().position(|s| s.team == 1).unwrap();
+        state.structures[hatchery].team = 0;
+        assert_eq!(state.outcome(), GameOutcome::Winner(0));
+
+        state.turn = 10;
+        assert_eq!(state.outcome(), GameOutcome::Ongoing);
+    }
+}
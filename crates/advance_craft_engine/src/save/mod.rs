@@ -0,0 +1,3 @@
+pub mod save;
+
+pub use save::*;
@@ -0,0 +1,150 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::{ScenarioState, replay::Replay};
+
+/// Bumped whenever the save format changes, with a matching step added to `migrate`.
+pub const SAVE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveMetadata {
+    pub name: String,
+    pub saved_at: u64, // Seconds since the Unix epoch
+    pub engine_version: String,
+    pub turn: u32,
+}
+
+/// A game in progress. The rules and random number generator are part of the state, and the
+/// replay holds the history of how the game got there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+    pub metadata: SaveMetadata,
+    pub scenario_state: ScenarioState,
+    pub replay: Option<Replay>,
+}
+
+#[derive(Debug)]
+pub enum SaveErr {
+    Malformed(serde_json::Error),
+    UnknownVersion(u64), // Written by a newer engine
+}
+
+impl SaveFile {
+    pub fn new(name: &str, scenario_state: &ScenarioState, replay: Option<Replay>) -> SaveFile {
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let mut scenario_state = scenario_state.clone();
+        scenario_state.undo_stack.clear();
+
+        SaveFile {
+            version: SAVE_VERSION,
+            metadata: SaveMetadata {
+                name: name.to_string(),
+                saved_at,
+                engine_version: env!("CARGO_PKG_VERSION").to_string(),
+                turn: scenario_state.turn,
+            },
+            scenario_state,
+            replay,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, SaveErr> {
+        serde_json::to_string(self).map_err(SaveErr::Malformed)
+    }
+
+    /// Reads a save written by this or any earlier version of the engine.
+    pub fn from_json(json: &str) -> Result<SaveFile, SaveErr> {
+        let mut value: Value = serde_json::from_str(json).map_err(SaveErr::Malformed)?;
+        migrate(&mut value)?;
+        serde_json::from_value(value).map_err(SaveErr::Malformed)
+    }
+}
+
+/// Upgrades a save to the current version one step at a time.
+fn migrate(value: &mut Value) -> Result<(), SaveErr> {
+    loop {
+        // Before save files had a version, games were saved as a bare state.
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+        match version {
+            0 => {
+                let turn = value.get("turn").cloned().unwrap_or(json!(1));
+                *value = json!({
+                    "version": 1,
+                    "metadata": {
+                        "name": "Unnamed",
+                        "saved_at": 0,
+                        "engine_version": "unknown",
+                        "turn": turn,
+                    },
+                    "scenario_state": value.take(),
+                    "replay": null,
+                });
+            }
+            version if version == SAVE_VERSION as u64 => return Ok(()),
+            version => return Err(SaveErr::UnknownVersion(version)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameRules, dev_helpers::new_scenario_state, replay::Replay};
+
+    #[test]
+    fn saves_round_trip() {
+        let mut state = new_scenario_state(GameRules::default());
+        let mut replay = Replay::new(&state);
+        let command = state.legal_commands(0).swap_remove(0);
+        let result = state.execute(command.clone());
+        replay.record(command, result);
+
+        let json = SaveFile::new("Test", &state, Some(replay))
+            .to_json()
+            .unwrap();
+        let loaded = SaveFile::from_json(&json).unwrap();
+
+        assert_eq!(loaded.metadata.name, "Test");
+        assert_eq!(loaded.scenario_state.units, state.units);
+        assert_eq!(loaded.scenario_state.state_hash(), state.state_hash());
+        assert!(loaded.replay.unwrap().verify().is_ok());
+    }
+
+    #[test]
+    fn version_one_saves_still_load() {
+        let save = SaveFile::from_json(include_str!("save_v1.json")).unwrap();
+
+        assert_eq!(save.metadata.name, "Fixture");
+        assert_eq!(save.scenario_state.units.len(), 2);
+        assert_eq!(save.scenario_state.team_states[&0].funds, 500);
+    }
+
+    #[test]
+    fn bare_states_are_migrated() {
+        let save = SaveFile::from_json(include_str!("save_v0.json")).unwrap();
+
+        assert_eq!(save.version, SAVE_VERSION);
+        assert_eq!(save.metadata.name, "Unnamed");
+        assert_eq!(save.metadata.turn, 3);
+        assert_eq!(save.scenario_state.turn, 3);
+        assert_eq!(save.scenario_state.units.len(), 2);
+        assert!(save.replay.is_none());
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        assert!(matches!(
+            SaveFile::from_json(r#"{"version": 99}"#),
+            Err(SaveErr::UnknownVersion(99))
+        ));
+        assert!(matches!(
+            SaveFile::from_json(r#"{"units": []}"#),
+            Err(SaveErr::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod status_effects;
+
+pub use status_effects::*;
@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Unit;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StatusEffectType {
+    Stimmed,
+    Slowed,
+    Burrowed,
+    Stunned,
+    Constructing,
+    Cocooned,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub effect_type: StatusEffectType,
+    // None lasts until the effect is removed (eg. unburrowing).
+    pub turns_remaining: Option<u32>,
+}
+
+impl StatusEffect {
+    pub fn new(effect_type: StatusEffectType, turns: u32) -> Self {
+        StatusEffect {
+            effect_type,
+            turns_remaining: Some(turns),
+        }
+    }
+
+    pub fn permanent(effect_type: StatusEffectType) -> Self {
+        StatusEffect {
+            effect_type,
+            turns_remaining: None,
+        }
+    }
+}
+
+pub struct StatusEffectStats {
+    pub name: &'static str,
+    pub damage_multiplier: f32,
+    pub movement_modifier: i32,
+    pub can_move: bool,
+    pub can_attack: bool,
+    pub requires_detection: bool,
+}
+
+impl StatusEffectType {
+    pub const fn value(&self) -> StatusEffectStats {
+        use StatusEffectType::*;
+        match *self {
+            Stimmed => STIMMED,
+            Slowed => SLOWED,
+            Burrowed => BURROWED,
+            Stunned => STUNNED,
+            Constructing => CONSTRUCTING,
+            Cocooned => COCOONED,
+        }
+    }
+}
+
+pub const STIMMED: StatusEffectStats = StatusEffectStats {
+    name: "Stimmed",
+    damage_multiplier: 1.5,
+    movement_modifier: 1,
+    can_move: true,
+    can_attack: true,
+    requires_detection: false,
+};
+
+pub const SLOWED: StatusEffectStats = StatusEffectStats {
+    name: "Slowed",
+    damage_multiplier: 1.0,
+    movement_modifier: -2,
+    can_move: true,
+    can_attack: true,
+    requires_detection: false,
+};
+
+pub const BURROWED: StatusEffectStats = StatusEffectStats {
+    name: "Burrowed",
+    damage_multiplier: 1.0,
+    movement_modifier: 0,
+    can_move: false,
+    can_attack: false,
+    requires_detection: true,
+};
+
+pub const STUNNED: StatusEffectStats = StatusEffectStats {
+    name: "Stunned",
+    damage_multiplier: 1.0,
+    movement_modifier: 0,
+    can_move: false,
+    can_attack: false,
+    requires_detection: false,
+};
+
+pub const CONSTRUCTING: StatusEffectStats = StatusEffectStats {
+    name: "Constructing",
+    damage_multiplier: 1.0,
+    movement_modifier: 0,
+    can_move: false,
+    can_attack: false,
+    requires_detection: false,
+};
+
+pub const COCOONED: StatusEffectStats = StatusEffectStats {
+    name: "Cocooned",
+    damage_multiplier: 1.0,
+    movement_modifier: 0,
+    can_move: false,
+    can_attack: false,
+    requires_detection: false,
+};
+
+/// At most one of each type, since reapplying an effect refreshes it.
+pub type StatusEffects = Vec<StatusEffect>;
+
+impl Unit {
+    pub fn status_effects(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.status_effects.iter()
+    }
+
+    pub fn has_status_effect(&self, effect_type: StatusEffectType) -> bool {
+        self.status_effects()
+            .any(|effect| effect.effect_type == effect_type)
+    }
+
+    /// Reapplying an effect the unit already has refreshes its duration.
+    pub fn apply_status_effect(&mut self, effect: StatusEffect) {
+        match self
+            .status_effects
+            .iter_mut()
+            .find(|existing| existing.effect_type == effect.effect_type)
+        {
+            Some(existing) => *existing = effect,
+            None => self.status_effects.push(effect),
+        }
+    }
+
+    pub fn remove_status_effect(&mut self, effect_type: StatusEffectType) {
+        self.status_effects
+            .retain(|effect| effect.effect_type != effect_type);
+    }
+
+    pub fn tick_status_effects(&mut self) {
+        self.status_effects
+            .retain_mut(|effect| match effect.turns_remaining {
+                Some(0) | Some(1) => false,
+                Some(turns) => {
+                    effect.turns_remaining = Some(turns - 1);
+                    true
+                }
+                None => true,
+            });
+    }
+
+    pub fn can_move(&self) -> bool {
+        self.status_effects()
+            .all(|effect| effect.effect_type.value().can_move)
+    }
+
+    pub fn can_attack(&self) -> bool {
+        self.status_effects()
+            .all(|effect| effect.effect_type.value().can_attack)
+    }
+
+    pub fn requires_detection(&self) -> bool {
+        self.status_effects()
+            .any(|effect| effect.effect_type.value().requires_detection)
+    }
+
+    pub fn movement_modifier(&self) -> i32 {
+        self.status_effects()
+            .map(|effect| effect.effect_type.value().movement_modifier)
+            .sum()
+    }
+
+    pub fn damage_multiplier(&self) -> f32 {
+        self.status_effects()
+            .map(|effect| effect.effect_type.value().damage_multiplier)
+            .product()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tile, UnitType};
+
+    fn unit() -> Unit {
+        Unit::new(0, UnitType::Zergling, Tile { x: 0, y: 0 }, 0)
+    }
+
+    #[test]
+    fn a_unit_can_have_every_effect_at_once() {
+        use StatusEffectType::*;
+        let mut unit = unit();
+        for effect_type in [Stimmed, Slowed, Burrowed, Stunned, Constructing, Cocooned] {
+            unit.apply_status_effect(StatusEffect::permanent(effect_type));
+        }
+        assert_eq!(unit.status_effects().count(), 6);
+    }
+
+    #[test]
+    fn reapplying_an_effect_refreshes_it() {
+        let mut unit = unit();
+        unit.apply_status_effect(StatusEffect::new(StatusEffectType::Stimmed, 1));
+        unit.apply_status_effect(StatusEffect::new(StatusEffectType::Stimmed, 3));
+
+        assert_eq!(unit.status_effects().count(), 1);
+        assert_eq!(unit.status_effects[0].turns_remaining, Some(3));
+    }
+
+    #[test]
+    fn effects_wear_off() {
+        let mut unit = unit();
+        unit.apply_status_effect(StatusEffect::new(StatusEffectType::Stunned, 2));
+        unit.apply_status_effect(StatusEffect::permanent(StatusEffectType::Burrowed));
+
+        unit.tick_status_effects();
+        assert!(!unit.can_move());
+        unit.tick_status_effects();
+        assert!(!unit.has_status_effect(StatusEffectType::Stunned));
+        assert!(unit.has_status_effect(StatusEffectType::Burrowed));
+    }
+}
@@ -0,0 +1,347 @@
+use crate::{
+    CommandErr, CommandResult, CommandStatus, ScenarioState, StatusEffect, StatusEffectType,
+    Structure, StructureId, StructureType, TeamID, TerrainType, Tile, UnitId,
+};
+
+impl Structure {
+    pub fn new(
+        id: StructureId,
+        structure_type: StructureType,
+        position: Tile,
+        team: TeamID,
+    ) -> Structure {
+        Structure {
+            id,
+            structure_type,
+            position,
+            health: structure_type.value().max_health,
+            team,
+            construction_turns_remaining: 0,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.construction_turns_remaining == 0
+    }
+
+    pub fn tiles(&self) -> Vec<Tile> {
+        footprint_tiles(self.structure_type, self.position)
+    }
+}
+
+/// The tiles covered by a structure placed with its bottom left corner at `position`.
+pub fn footprint_tiles(structure_type: StructureType, position: Tile) -> Vec<Tile> {
+    let (width, height) = structure_type.value().footprint;
+    let mut tiles = vec![];
+    for dy in 0..height {
+        for dx in 0..width {
+            tiles.push(Tile {
+                x: position.x + dx,
+                y: position.y + dy,
+            });
+        }
+    }
+    tiles
+}
+
+impl ScenarioState {
+    pub fn get_structure_at(&self, x: u32, y: u32) -> Option<&Structure> {
+        let tile = Tile { x, y };
+        self.structures
+            .iter()
+            .find(|structure| structure.tiles().contains(&tile))
+    }
+
+    pub fn next_structure_id(&self) -> StructureId {
+        self.structures
+            .iter()
+            .map(|s| s.id)
+            .max()
+            .map_or(0, |id| id + 1)
+    }
+
+    pub fn has_creep(&self, team: TeamID, tile: &Tile) -> bool {
+        self.creep
+            .0
+            .get(&team)
+            .and_then(|creep_map| creep_map.get(tile.y as usize))
+            .and_then(|row| row.get(tile.x as usize))
+            .is_some_and(|has_creep| *has_creep)
+    }
+
+    pub fn check_placement(
+        &self,
+        worker_id: UnitId,
+        structure_type: StructureType,
+        position: Tile,
+    ) -> Result<(), CommandErr> {
+        let worker = self.get_unit(worker_id).expect("No worker found");
+        let stats = structure_type.value();
+        let tiles = footprint_tiles(structure_type, position);
+
+        for tile in tiles.iter() {
+            if self
+                .is_tile_within_bounds(tile.x as i32, tile.y as i32)
+                .is_err()
+            {
+                return Err(CommandErr::InvalidPlacement);
+            }
+
+            let terrain = self.map[tile.y as usize][tile.x as usize];
+            if !matches!(terrain, TerrainType::Grass) {
+                return Err(CommandErr::InvalidPlacement);
+            }
+
+            if self.get_structure_at(tile.x, tile.y).is_some()
+                || self.is_tile_occupied(worker_id, tile.x, tile.y)
+            {
+                return Err(CommandErr::TileOccupied);
+            }
+
+            if stats.requires_creep && !self.has_creep(worker.team, tile) {
+                return Err(CommandErr::RequiresCreep);
+            }
+
+            if stats.requires_power && !self.has_power(worker.team, tile) {
+                return Err(CommandErr::Unpowered);
+            }
+        }
+
+        let is_adjacent = tiles
+            .iter()
+            .any(|tile| self.in_range(tile, &worker.position, 0.0, 1.0));
+        if !is_adjacent {
+            return Err(CommandErr::OutOfRange);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn construct(
+        &mut self,
+        worker_id: UnitId,
+        structure_type: StructureType,
+        tile: Tile,
+    ) -> CommandResult {
+        let worker = self.get_unit(worker_id).expect("No worker found");
+        let team = worker.team;
+        let funds = self.team_states.get(&team).map_or(0, |t| t.funds);
+
+        let error = |err: CommandErr| CommandResult::Construct {
+            status: CommandStatus::Err(err),
+            structure: None,
+            consumed_worker: None,
+            funds,
+        };
+
+        if team != self.active_team {
+            return error(CommandErr::WrongTeam);
+        }
+
+        if worker.has_attacked || !worker.can_move() {
+            return error(CommandErr::Incapacitated);
+        }
+
+        let stats = structure_type.value();
+        if stats.built_by != Some(worker.unit_type) {
+            return error(CommandErr::CannotProduce);
+        }
+
+        if !self
+            .missing_requirements(team, &stats.requirements)
+            .is_empty()
+        {
+            return error(CommandErr::RequirementsNotMet);
+        }
+
+        if funds < stats.cost {
+            return error(CommandErr::InsufficientFunds);
+        }
+
+        if let Err(err) = self.check_placement(worker_id, structure_type, tile) {
+            return error(err);
+        }
+
+        let mut structure = Structure::new(self.next_structure_id(), structure_type, tile, team);
+        structure.construction_turns_remaining = stats.build_turns;
+        self.structures.push(structure);
+
+        let team_state = self.team_states.entry(team).or_default();
+        team_state.funds -= stats.cost;
+        let funds = team_state.funds;
+
+        let consumed_worker = if stats.consumes_builder {
+            self.units.retain(|unit| unit.id != worker_id);
+            Some(worker_id)
+        } else {
+            let worker = self.units.iter_mut().find(|u| u.id == worker_id).unwrap();
+            worker.apply_status_effect(StatusEffect::new(
+                StatusEffectType::Constructing,
+                stats.build_turns,
+            ));
+            worker.has_moved = true;
+            worker.has_attacked = true;
+            None
+        };
+
+        CommandResult::Construct {
+            status: CommandStatus::Ok,
+            structure: Some(structure),
+            consumed_worker,
+            funds,
+        }
+    }
+
+    pub(crate) fn progress_construction(&mut self, team: TeamID) -> Vec<StructureId> {
+        let mut completed = vec![];
+        for structure in self.structures.iter_mut() {
+            if structure.team != team || structure.is_complete() {
+                continue;
+            }
+            structure.construction_turns_remaining -= 1;
+            if structure.is_complete() {
+                completed.push(structure.id);
+            }
+        }
+
+        for structure_id in completed.iter() {
+            self.spread_creep(*structure_id);
+        }
+
+        completed
+    }
+
+    fn spread_creep(&mut self, structure_id: StructureId) {
+        let structure = *self.get_structure(structure_id).unwrap();
+        let Some(radius) = structure.structure_type.value().creep_radius else {
+            return;
+        };
+        let tiles: Vec<Tile> = self
+            .tiles_in_range(structure.position, 0.0, radius)
+            .collect();
+        let Some(creep_map) = self.creep.0.get_mut(&structure.team) else {
+            return;
+        };
+
+        for tile in tiles {
+            creep_map[tile.y as usize][tile.x as usize] = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, GameRules, UnitType, dev_helpers::new_scenario_with_units};
+
+    fn construct(
+        state: &mut ScenarioState,
+        worker_id: UnitId,
+        structure_type: StructureType,
+        (x, y): (u32, u32),
+    ) -> CommandResult {
+        state.execute(Command::Construct {
+            worker_id,
+            structure_type,
+            tile: Tile { x, y },
+        })
+    }
+
+    #[test]
+    fn workers_stay_busy_until_construction_finishes() {
+        let mut state =
+            new_scenario_with_units(GameRules::default(), &[(UnitType::Scv, (4, 0), 0)]);
+        let funds = state.team_states[&0].funds;
+        let stats = StructureType::Barracks.value();
+
+        let CommandResult::Construct {
+            status,
+            structure: Some(barracks),
+            ..
+        } = construct(&mut state, 0, StructureType::Barracks, (2, 0))
+        else {
+            panic!("The barracks wasn't placed");
+        };
+        assert_eq!(status, CommandStatus::Ok);
+        assert_eq!(state.team_states[&0].funds, funds - stats.cost);
+        assert!(!state.get_structure(barracks.id).unwrap().is_complete());
+
+        for _ in 0..stats.build_turns {
+            assert!(!state.get_unit(0).unwrap().can_move());
+            state.execute(Command::EndTurn);
+            state.execute(Command::EndTurn);
+        }
+        assert!(state.get_structure(barracks.id).unwrap().is_complete());
+        assert!(state.get_unit(0).unwrap().can_move());
+    }
+
+    #[test]
+    fn zerg_structures_need_creep_and_consume_the_drone() {
+        let mut state = new_scenario_with_units(
+            GameRules::default(),
+            &[(UnitType::Drone, (7, 0), 1), (UnitType::Drone, (5, 0), 1)],
+        );
+        state.active_team = 1;
+
+        let result = construct(&mut state, 0, StructureType::SpawningPool, (8, 0));
+        assert_eq!(
+            result.status(),
+            CommandStatus::Err(CommandErr::RequiresCreep)
+        );
+
+        let CommandResult::Construct {
+            status,
+            consumed_worker,
+            ..
+        } = construct(&mut state, 1, StructureType::SpawningPool, (6, 0))
+        else {
+            unreachable!();
+        };
+        assert_eq!(status, CommandStatus::Ok);
+        assert_eq!(consumed_worker, Some(1));
+        assert!(state.get_unit(1).is_none());
+    }
+
+    #[test]
+    fn structures_must_be_placed_next_to_the_worker() {
+        let mut state =
+            new_scenario_with_units(GameRules::default(), &[(UnitType::Scv, (4, 0), 0)]);
+
+        let result = construct(&mut state, 0, StructureType::Barracks, (6, 2));
+        assert_eq!(result.status(), CommandStatus::Err(CommandErr::OutOfRange));
+
+        let result = construct(&mut state, 0, StructureType::Barracks, (3, 2));
+        assert_eq!(
+            result.status(),
+            CommandStatus::Err(CommandErr::InvalidPlacement)
+        );
+    }
+
+    #[test]
+    fn large_structures_need_every_tile_free() {
+        let mut state = new_scenario_with_units(
+            GameRules::default(),
+            &[(UnitType::Scv, (4, 0), 0), (UnitType::Marine, (2, 1), 0)],
+        );
+        assert_eq!(StructureType::Barracks.value().footprint, (2, 2));
+
+        // Overlapping a hatchery, the mountain or another unit
+        for (position, err) in [
+            ((4, 1), CommandErr::TileOccupied),
+            ((3, 1), CommandErr::InvalidPlacement),
+            ((2, 0), CommandErr::TileOccupied),
+        ] {
+            let result = construct(&mut state, 0, StructureType::Barracks, position);
+            assert_eq!(result.status(), CommandStatus::Err(err));
+        }
+
+        // Only one of its tiles needs to be next to the worker.
+        state.units.retain(|unit| unit.id == 0);
+        let result = construct(&mut state, 0, StructureType::Barracks, (2, 0));
+        assert_eq!(result.status(), CommandStatus::Ok);
+        for (x, y) in [(2, 0), (3, 0), (2, 1), (3, 1)] {
+            let structure = state.get_structure_at(x, y).unwrap();
+            assert_eq!(structure.structure_type, StructureType::Barracks);
+        }
+    }
+}
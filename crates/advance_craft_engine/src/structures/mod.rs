@@ -1,3 +1,6 @@
+pub mod construction;
+pub mod power;
 pub mod structures;
 
+pub use construction::*;
 pub use structures::*;
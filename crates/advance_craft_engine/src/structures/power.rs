@@ -0,0 +1,33 @@
+use crate::{ScenarioState, Structure, StructureType, TeamID, Tile};
+
+impl ScenarioState {
+    /// Whether a completed Pylon belonging to `team` powers this tile.
+    pub fn has_power(&self, team: TeamID, tile: &Tile) -> bool {
+        self.structures.iter().any(|structure| {
+            structure.team == team
+                && structure.is_complete()
+                && structure
+                    .structure_type
+                    .value()
+                    .power_radius
+                    .is_some_and(|radius| self.distance(&structure.position, tile) <= radius)
+        })
+    }
+
+    pub fn is_powered(&self, structure: &Structure) -> bool {
+        !structure.structure_type.value().requires_power
+            || structure
+                .tiles()
+                .iter()
+                .all(|tile| self.has_power(structure.team, tile))
+    }
+
+    pub fn owns_powered_structure(&self, team: TeamID, structure_type: StructureType) -> bool {
+        self.structures.iter().any(|s| {
+            s.team == team
+                && s.structure_type == structure_type
+                && s.is_complete()
+                && self.is_powered(s)
+        })
+    }
+}
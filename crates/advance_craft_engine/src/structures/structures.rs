@@ -1,13 +1,29 @@
 use serde::{Deserialize, Serialize};
 
-use crate::DamageTag;
+use crate::{DamageTag, Requirement, Requirements, UnitType};
 
 use crate::units::weapon::*;
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum StructureType {
     Hatchery,
     CommandCentre,
+    Barracks,
+    Factory,
+    EngineeringBay,
+    SpawningPool,
+    RoachWarren,
+    EvolutionChamber,
+    BanelingNest,
+    Nexus,
+    Pylon,
+    Gateway,
+    CyberneticsCore,
+    RoboticsFacility,
+    Spire,
+    Starport,
+    NydusNetwork,
+    SupplyDepot,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -19,21 +35,71 @@ pub struct StructureStats {
     pub tags: [Option<DamageTag>; 4],
     pub base_armour: f32,
     pub weapon_one: Option<Weapon>,
+    pub detection_range: Option<f32>, // Reveals burrowed units this close to any of its tiles
+    pub cost: u32,
+    pub requirements: Requirements,
+    pub footprint: (u32, u32), // Width, Height
+    pub build_turns: u32,
+    pub built_by: Option<UnitType>,
+    pub consumes_builder: bool,
+    pub requires_creep: bool,
+    pub creep_radius: Option<f32>,
+    pub power_radius: Option<f32>,
+    pub requires_power: bool, // Disabled unless within a Pylon's power field
+    pub cargo_capacity: u32,  // Shared between every structure of this type on a team
+    pub supply_provided: u32,
 }
 
 #[derive(Serialize, Deserialize)]
 pub enum Race {
     Zerg,
     Terran,
-    _Protoss,
+    Protoss,
 }
 
 impl StructureType {
+    pub const ALL: [StructureType; 18] = [
+        StructureType::CommandCentre,
+        StructureType::SupplyDepot,
+        StructureType::Barracks,
+        StructureType::Factory,
+        StructureType::Starport,
+        StructureType::EngineeringBay,
+        StructureType::Hatchery,
+        StructureType::SpawningPool,
+        StructureType::RoachWarren,
+        StructureType::EvolutionChamber,
+        StructureType::BanelingNest,
+        StructureType::Spire,
+        StructureType::NydusNetwork,
+        StructureType::Nexus,
+        StructureType::Pylon,
+        StructureType::Gateway,
+        StructureType::CyberneticsCore,
+        StructureType::RoboticsFacility,
+    ];
+
     pub const fn value(&self) -> StructureStats {
         use StructureType::*;
         match *self {
             CommandCentre => COMMAND_CENTRE,
             Hatchery => HATCHERY,
+            Barracks => BARRACKS,
+            Factory => FACTORY,
+            EngineeringBay => ENGINEERING_BAY,
+            SpawningPool => SPAWNING_POOL,
+            RoachWarren => ROACH_WARREN,
+            EvolutionChamber => EVOLUTION_CHAMBER,
+            BanelingNest => BANELING_NEST,
+            Nexus => NEXUS,
+            Pylon => PYLON,
+            Gateway => GATEWAY,
+            CyberneticsCore => CYBERNETICS_CORE,
+            RoboticsFacility => ROBOTICS_FACILITY,
+            Spire => SPIRE,
+            Starport => STARPORT,
+            NydusNetwork => NYDUS_NETWORK,
+            SupplyDepot => SUPPLY_DEPOT,
         }
     }
 }
@@ -49,14 +115,429 @@ pub const HATCHERY: StructureStats = StructureStats {
     tags: [Some(Ground), Some(Armoured), Some(Structure), None],
     base_armour: 1.0,
     weapon_one: Some(ZERGLING_ATTACK),
+    detection_range: Some(3.0),
+    cost: 300,
+    requirements: [None, None],
+    footprint: (1, 1),
+    build_turns: 4,
+    built_by: Some(UnitType::Drone),
+    consumes_builder: true,
+    requires_creep: false,
+    creep_radius: Some(2.0),
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 6,
 };
 
 pub const COMMAND_CENTRE: StructureStats = StructureStats {
     id: 1,
-    race: Zerg,
+    race: Terran,
     name: "Command Center",
     max_health: 1500.0,
     tags: [Some(Ground), Some(Armoured), Some(Structure), None],
     base_armour: 1.0,
     weapon_one: Some(ZERGLING_ATTACK),
+    detection_range: Some(3.0),
+    cost: 400,
+    requirements: [None, None],
+    footprint: (1, 1),
+    build_turns: 4,
+    built_by: Some(UnitType::Scv),
+    consumes_builder: false,
+    requires_creep: false,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 15,
+};
+
+pub const BARRACKS: StructureStats = StructureStats {
+    id: 2,
+    race: Terran,
+    name: "Barracks",
+    max_health: 1000.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 150,
+    requirements: [
+        Some(Requirement::Structure(StructureType::CommandCentre)),
+        None,
+    ],
+    footprint: (2, 2),
+    build_turns: 3,
+    built_by: Some(UnitType::Scv),
+    consumes_builder: false,
+    requires_creep: false,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const FACTORY: StructureStats = StructureStats {
+    id: 3,
+    race: Terran,
+    name: "Factory",
+    max_health: 1250.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 200,
+    requirements: [Some(Requirement::Structure(StructureType::Barracks)), None],
+    footprint: (2, 2),
+    build_turns: 3,
+    built_by: Some(UnitType::Scv),
+    consumes_builder: false,
+    requires_creep: false,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const ENGINEERING_BAY: StructureStats = StructureStats {
+    id: 4,
+    race: Terran,
+    name: "Engineering Bay",
+    max_health: 850.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 125,
+    requirements: [
+        Some(Requirement::Structure(StructureType::CommandCentre)),
+        None,
+    ],
+    footprint: (1, 1),
+    build_turns: 2,
+    built_by: Some(UnitType::Scv),
+    consumes_builder: false,
+    requires_creep: false,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const SPAWNING_POOL: StructureStats = StructureStats {
+    id: 2,
+    race: Zerg,
+    name: "Spawning Pool",
+    max_health: 1000.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 200,
+    requirements: [Some(Requirement::Structure(StructureType::Hatchery)), None],
+    footprint: (1, 1),
+    build_turns: 3,
+    built_by: Some(UnitType::Drone),
+    consumes_builder: true,
+    requires_creep: true,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const ROACH_WARREN: StructureStats = StructureStats {
+    id: 3,
+    race: Zerg,
+    name: "Roach Warren",
+    max_health: 850.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 150,
+    requirements: [
+        Some(Requirement::Structure(StructureType::SpawningPool)),
+        None,
+    ],
+    footprint: (1, 1),
+    build_turns: 2,
+    built_by: Some(UnitType::Drone),
+    consumes_builder: true,
+    requires_creep: true,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const EVOLUTION_CHAMBER: StructureStats = StructureStats {
+    id: 4,
+    race: Zerg,
+    name: "Evolution Chamber",
+    max_health: 750.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 75,
+    requirements: [Some(Requirement::Structure(StructureType::Hatchery)), None],
+    footprint: (1, 1),
+    build_turns: 2,
+    built_by: Some(UnitType::Drone),
+    consumes_builder: true,
+    requires_creep: true,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const BANELING_NEST: StructureStats = StructureStats {
+    id: 5,
+    race: Zerg,
+    name: "Baneling Nest",
+    max_health: 850.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 100,
+    requirements: [
+        Some(Requirement::Structure(StructureType::SpawningPool)),
+        None,
+    ],
+    footprint: (1, 1),
+    build_turns: 2,
+    built_by: Some(UnitType::Drone),
+    consumes_builder: true,
+    requires_creep: true,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const NEXUS: StructureStats = StructureStats {
+    id: 1,
+    race: Protoss,
+    name: "Nexus",
+    max_health: 1000.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: Some(3.0),
+    cost: 400,
+    requirements: [None, None],
+    footprint: (1, 1),
+    build_turns: 4,
+    built_by: Some(UnitType::Probe),
+    consumes_builder: false,
+    requires_creep: false,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 15,
+};
+
+pub const PYLON: StructureStats = StructureStats {
+    id: 2,
+    race: Protoss,
+    name: "Pylon",
+    max_health: 200.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 100,
+    requirements: [None, None],
+    footprint: (1, 1),
+    build_turns: 1,
+    built_by: Some(UnitType::Probe),
+    consumes_builder: false,
+    requires_creep: false,
+    creep_radius: None,
+    power_radius: Some(3.0),
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 8,
+};
+
+pub const GATEWAY: StructureStats = StructureStats {
+    id: 3,
+    race: Protoss,
+    name: "Gateway",
+    max_health: 500.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 150,
+    requirements: [Some(Requirement::Structure(StructureType::Nexus)), None],
+    footprint: (2, 2),
+    build_turns: 3,
+    built_by: Some(UnitType::Probe),
+    consumes_builder: false,
+    requires_creep: false,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: true,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const CYBERNETICS_CORE: StructureStats = StructureStats {
+    id: 4,
+    race: Protoss,
+    name: "Cybernetics Core",
+    max_health: 550.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 150,
+    requirements: [Some(Requirement::Structure(StructureType::Gateway)), None],
+    footprint: (1, 1),
+    build_turns: 2,
+    built_by: Some(UnitType::Probe),
+    consumes_builder: false,
+    requires_creep: false,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: true,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const ROBOTICS_FACILITY: StructureStats = StructureStats {
+    id: 5,
+    race: Protoss,
+    name: "Robotics Facility",
+    max_health: 450.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 250,
+    requirements: [
+        Some(Requirement::Structure(StructureType::CyberneticsCore)),
+        None,
+    ],
+    footprint: (2, 2),
+    build_turns: 3,
+    built_by: Some(UnitType::Probe),
+    consumes_builder: false,
+    requires_creep: false,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: true,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const SPIRE: StructureStats = StructureStats {
+    id: 6,
+    race: Zerg,
+    name: "Spire",
+    max_health: 850.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 200,
+    requirements: [
+        Some(Requirement::Structure(StructureType::SpawningPool)),
+        None,
+    ],
+    footprint: (1, 1),
+    build_turns: 3,
+    built_by: Some(UnitType::Drone),
+    consumes_builder: true,
+    requires_creep: true,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const STARPORT: StructureStats = StructureStats {
+    id: 5,
+    race: Terran,
+    name: "Starport",
+    max_health: 1300.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 250,
+    requirements: [Some(Requirement::Structure(StructureType::Factory)), None],
+    footprint: (2, 2),
+    build_turns: 3,
+    built_by: Some(UnitType::Scv),
+    consumes_builder: false,
+    requires_creep: false,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 0,
+};
+
+pub const NYDUS_NETWORK: StructureStats = StructureStats {
+    id: 7,
+    race: Zerg,
+    name: "Nydus Network",
+    max_health: 850.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 200,
+    requirements: [
+        Some(Requirement::Structure(StructureType::SpawningPool)),
+        None,
+    ],
+    footprint: (1, 1),
+    build_turns: 3,
+    built_by: Some(UnitType::Drone),
+    consumes_builder: true,
+    requires_creep: true,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 20,
+    supply_provided: 0,
+};
+
+pub const SUPPLY_DEPOT: StructureStats = StructureStats {
+    id: 6,
+    race: Terran,
+    name: "Supply Depot",
+    max_health: 400.0,
+    tags: [Some(Ground), Some(Armoured), Some(Structure), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    detection_range: None,
+    cost: 100,
+    requirements: [None, None],
+    footprint: (1, 1),
+    build_turns: 1,
+    built_by: Some(UnitType::Scv),
+    consumes_builder: false,
+    requires_creep: false,
+    creep_radius: None,
+    power_radius: None,
+    requires_power: false,
+    cargo_capacity: 0,
+    supply_provided: 8,
 };
@@ -0,0 +1,5 @@
+pub mod supply;
+pub mod tech;
+
+pub use supply::*;
+pub use tech::*;
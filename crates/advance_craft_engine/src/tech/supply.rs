@@ -0,0 +1,129 @@
+use crate::{ScenarioState, TeamID, Unit};
+
+pub const MAX_SUPPLY: u32 = 200;
+
+impl ScenarioState {
+    /// Every unit belonging to the team, including those inside transports.
+    fn team_units(&self, team: TeamID) -> Vec<&Unit> {
+        let mut units: Vec<&Unit> = vec![];
+        for unit in self.units.iter().filter(|unit| unit.team == team) {
+            units.push(unit);
+            units.extend(unit.cargo.iter());
+        }
+        if let Some(team_state) = self.team_states.get(&team) {
+            units.extend(team_state.nydus_network.iter());
+        }
+        units
+    }
+
+    pub fn supply_used(&self, team: TeamID) -> u32 {
+        self.team_units(team)
+            .iter()
+            .map(|unit| {
+                // Cocoons already take up the supply of what they are morphing into.
+                let unit_type = unit.morph.map_or(unit.unit_type, |morph| morph.into);
+                unit_type.value().supply_cost
+            })
+            .sum()
+    }
+
+    pub fn supply_max(&self, team: TeamID) -> u32 {
+        let from_units: u32 = self
+            .team_units(team)
+            .iter()
+            .map(|unit| unit.unit_type.value().supply_provided)
+            .sum();
+        let from_structures: u32 = self
+            .structures
+            .iter()
+            .filter(|s| s.team == team && s.is_complete())
+            .map(|s| s.structure_type.value().supply_provided)
+            .sum();
+        (from_units + from_structures).min(MAX_SUPPLY)
+    }
+
+    pub fn has_supply_for(&self, team: TeamID, supply_cost: u32) -> bool {
+        self.supply_used(team) + supply_cost <= self.supply_max(team)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Command, CommandErr, CommandStatus, GameRules, Structure, StructureType, Tile, UnitType,
+        dev_helpers::new_scenario_with_units, tech::LockReason, units::MorphInProgress,
+    };
+
+    /// Terran bases with enough zealots to use every bit of their supply.
+    fn supply_blocked_state() -> ScenarioState {
+        let supply = 2 * StructureType::CommandCentre.value().supply_provided;
+        let zealots: Vec<_> = (0..supply / UnitType::Zealot.value().supply_cost)
+            .map(|i| (UnitType::Zealot, (i % 9, i / 9), 0))
+            .collect();
+        new_scenario_with_units(GameRules::default(), &zealots)
+    }
+
+    #[test]
+    fn supply_counts_carried_units_and_cocoons() {
+        let mut state = new_scenario_with_units(
+            GameRules::default(),
+            &[(UnitType::Medivac, (2, 0), 0), (UnitType::Roach, (6, 0), 1)],
+        );
+        let marine = Unit::new(10, UnitType::Marine, Tile { x: 2, y: 0 }, 0);
+        state.units[0].cargo.push(marine);
+        assert_eq!(
+            state.supply_used(0),
+            UnitType::Medivac.value().supply_cost + UnitType::Marine.value().supply_cost
+        );
+
+        state.units[1].morph = Some(MorphInProgress {
+            into: UnitType::Ravager,
+            turns_remaining: 1,
+        });
+        assert_eq!(state.supply_used(1), UnitType::Ravager.value().supply_cost);
+    }
+
+    #[test]
+    fn only_finished_structures_provide_supply_up_to_the_cap() {
+        let mut state = new_scenario_with_units(GameRules::default(), &[]);
+        let supply = state.supply_max(0);
+
+        let mut depot = Structure::new(10, StructureType::SupplyDepot, Tile { x: 0, y: 0 }, 0);
+        depot.construction_turns_remaining = 1;
+        state.structures.push(depot);
+        assert_eq!(state.supply_max(0), supply);
+
+        for id in 11..11 + MAX_SUPPLY {
+            let centre = Structure::new(id, StructureType::CommandCentre, Tile { x: 0, y: 0 }, 0);
+            state.structures.push(centre);
+        }
+        assert_eq!(state.supply_max(0), MAX_SUPPLY);
+    }
+
+    #[test]
+    fn production_stops_at_the_supply_limit() {
+        let mut state = supply_blocked_state();
+        assert_eq!(state.supply_used(0), state.supply_max(0));
+
+        let result = state.execute(Command::Build {
+            structure_id: 0,
+            unit_type: UnitType::Scv,
+        });
+        assert_eq!(
+            result.status(),
+            CommandStatus::Err(CommandErr::SupplyBlocked)
+        );
+
+        let scv = state
+            .production_options(0)
+            .into_iter()
+            .find(|option| option.unit_type == UnitType::Scv)
+            .unwrap();
+        let shortfall = UnitType::Scv.value().supply_cost;
+        assert!(
+            scv.locked_by
+                .contains(&LockReason::SupplyBlocked(shortfall))
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ResearchType, ScenarioState, StructureType, TeamID, UnitType};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Requirement {
+    Structure(StructureType),
+    Research(ResearchType, u32), // Minimum level
+}
+
+pub type Requirements = [Option<Requirement>; 2];
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LockReason {
+    Missing(Requirement),
+    NoProductionStructure(StructureType),
+    NotProducible, // Only available by morphing
+    Unpowered(StructureType),
+
+    InsufficientFunds(u32), // Shortfall
+    SupplyBlocked(u32),     // Shortfall
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProductionOption {
+    pub unit_type: UnitType,
+    pub cost: u32,
+    pub locked_by: Vec<LockReason>,
+}
+
+impl ProductionOption {
+    pub fn is_available(&self) -> bool {
+        self.locked_by.is_empty()
+    }
+}
+
+impl ScenarioState {
+    pub fn owns_structure(&self, team: TeamID, structure_type: StructureType) -> bool {
+        self.structures
+            .iter()
+            .any(|s| s.team == team && s.structure_type == structure_type && s.is_complete())
+    }
+
+    pub fn is_requirement_met(&self, team: TeamID, requirement: &Requirement) -> bool {
+        match requirement {
+            Requirement::Structure(structure_type) => self.owns_structure(team, *structure_type),
+            Requirement::Research(research, level) => self
+                .team_states
+                .get(&team)
+                .and_then(|t| t.upgrades.get(research))
+                .is_some_and(|researched| researched >= level),
+        }
+    }
+
+    pub fn missing_requirements(
+        &self,
+        team: TeamID,
+        requirements: &Requirements,
+    ) -> Vec<Requirement> {
+        requirements
+            .iter()
+            .flatten()
+            .filter(|requirement| !self.is_requirement_met(team, requirement))
+            .copied()
+            .collect()
+    }
+
+    /// Every unit type, and anything preventing the team from building it right now.
+    pub fn production_options(&self, team: TeamID) -> Vec<ProductionOption> {
+        let funds = self.team_states.get(&team).map_or(0, |t| t.funds);
+
+        UnitType::ALL
+            .iter()
+            .map(|unit_type| {
+                let stats = unit_type.value();
+                let mut locked_by: Vec<LockReason> = self
+                    .missing_requirements(team, &stats.requirements)
+                    .into_iter()
+                    .map(LockReason::Missing)
+                    .collect();
+
+                match stats.produced_at {
+                    Some(structure_type) if !self.owns_structure(team, structure_type) => {
+                        locked_by.push(LockReason::NoProductionStructure(structure_type))
+                    }
+                    Some(structure_type) if !self.owns_powered_structure(team, structure_type) => {
+                        locked_by.push(LockReason::Unpowered(structure_type))
+                    }
+                    None => locked_by.push(LockReason::NotProducible),
+                    _ => {}
+                }
+
+                if funds < stats.cost {
+                    locked_by.push(LockReason::InsufficientFunds(stats.cost - funds));
+                }
+
+                if !self.has_supply_for(team, stats.supply_cost) {
+                    let shortfall =
+                        self.supply_used(team) + stats.supply_cost - self.supply_max(team);
+                    locked_by.push(LockReason::SupplyBlocked(shortfall));
+                }
+
+                ProductionOption {
+                    unit_type: *unit_type,
+                    cost: stats.cost,
+                    locked_by,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Command, CommandErr, CommandStatus, GameRules, Structure, Tile,
+        dev_helpers::new_scenario_with_units,
+    };
+
+    const HATCHERY_ID: u32 = 2;
+
+    /// The dev scenario's bases with no units, on the zerg turn.
+    fn state() -> ScenarioState {
+        let mut state = new_scenario_with_units(GameRules::default(), &[]);
+        state.active_team = 1;
+        state
+    }
+
+    fn add_pool(state: &mut ScenarioState, construction_turns_remaining: u32) {
+        let mut pool = Structure::new(10, StructureType::SpawningPool, Tile { x: 6, y: 0 }, 1);
+        pool.construction_turns_remaining = construction_turns_remaining;
+        state.structures.push(pool);
+    }
+
+    fn build_zergling(state: &mut ScenarioState) -> CommandStatus {
+        state
+            .execute(Command::Build {
+                structure_id: HATCHERY_ID,
+                unit_type: UnitType::Zergling,
+            })
+            .status()
+    }
+
+    fn zergling_option(state: &ScenarioState) -> ProductionOption {
+        state
+            .production_options(1)
+            .into_iter()
+            .find(|option| option.unit_type == UnitType::Zergling)
+            .unwrap()
+    }
+
+    #[test]
+    fn units_need_their_requirements() {
+        let mut state = state();
+
+        let pool = Requirement::Structure(StructureType::SpawningPool);
+        assert_eq!(
+            zergling_option(&state).locked_by,
+            vec![LockReason::Missing(pool)]
+        );
+        assert_eq!(
+            build_zergling(&mut state),
+            CommandStatus::Err(CommandErr::RequirementsNotMet)
+        );
+
+        add_pool(&mut state, 0);
+        assert!(zergling_option(&state).is_available());
+        assert_eq!(build_zergling(&mut state), CommandStatus::Ok);
+    }
+
+    #[test]
+    fn unfinished_structures_are_not_requirements() {
+        let mut state = state();
+        add_pool(&mut state, 2);
+
+        assert!(!zergling_option(&state).is_available());
+        assert_eq!(
+            build_zergling(&mut state),
+            CommandStatus::Err(CommandErr::RequirementsNotMet)
+        );
+    }
+
+    #[test]
+    fn morphed_units_cannot_be_built() {
+        let state = state();
+        let baneling = state
+            .production_options(1)
+            .into_iter()
+            .find(|option| option.unit_type == UnitType::Baneling)
+            .unwrap();
+        assert!(baneling.locked_by.contains(&LockReason::NotProducible));
+    }
+}
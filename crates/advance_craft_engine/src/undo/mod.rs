@@ -0,0 +1,3 @@
+pub mod undo;
+
+pub use undo::*;
@@ -0,0 +1,448 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Command, CommandResult, Creep, Rng, ScenarioState, Structure, StructureId, TeamID, TeamState,
+    Unit, UnitId,
+    rules::UndoPolicy,
+    units::{RepairTarget, Transport},
+};
+
+/// Copies of the entities in a list which a command might change, taken before it runs.
+struct Snapshot<T> {
+    ids: HashSet<u32>,        // Every entity in the list
+    touched: Vec<(usize, T)>, // The ones the command may change, and where they were
+}
+
+impl<T: Clone> Snapshot<T> {
+    /// `touched` of None copies the whole list.
+    fn take(items: &[T], id: fn(&T) -> u32, touched: Option<&[u32]>) -> Snapshot<T> {
+        Snapshot {
+            ids: items.iter().map(id).collect(),
+            touched: items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| touched.is_none_or(|touched| touched.contains(&id(item))))
+                .map(|(index, item)| (index, item.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// How one list of entities changed, kept so the change can be reverted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ListDelta<T> {
+    added: Vec<u32>,          // Ids which didn't exist before
+    changed: Vec<T>,          // Earlier versions of entities which were modified
+    removed: Vec<(usize, T)>, // Entities which no longer exist, and where they were in the list
+}
+
+impl<T: Clone + PartialEq> ListDelta<T> {
+    fn between(before: Snapshot<T>, after: &[T], id: fn(&T) -> u32) -> ListDelta<T> {
+        let after_by_id: HashMap<u32, &T> = after.iter().map(|item| (id(item), item)).collect();
+
+        let mut changed = vec![];
+        let mut removed = vec![];
+        for (index, item) in before.touched {
+            match after_by_id.get(&id(&item)) {
+                Some(now) if **now == item => {}
+                Some(_) => changed.push(item),
+                None => removed.push((index, item)),
+            }
+        }
+
+        ListDelta {
+            added: after
+                .iter()
+                .map(id)
+                .filter(|item_id| !before.ids.contains(item_id))
+                .collect(),
+            changed,
+            removed,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    // Commands never reorder the entities they leave alone, so putting removed entities back
+    // at their old indices, lowest first, restores the original order.
+    fn revert(&self, items: &mut Vec<T>, id: fn(&T) -> u32) {
+        items.retain(|item| !self.added.contains(&id(item)));
+        for old in self.changed.iter() {
+            if let Some(item) = items.iter_mut().find(|item| id(item) == id(old)) {
+                *item = old.clone();
+            }
+        }
+        for (index, old) in self.removed.iter() {
+            items.insert((*index).min(items.len()), old.clone());
+        }
+    }
+}
+
+/// The inverse of one command: everything needed to put the state back the way it was.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delta {
+    units: ListDelta<Unit>,
+    structures: ListDelta<Structure>,
+    team_states: Option<HashMap<TeamID, TeamState>>,
+    creep: Option<Creep>,
+    active_team: TeamID,
+    turn: u32,
+    rng: Rng,
+    pub reveals_information: bool, // The command showed the team something it couldn't see before
+}
+
+/// The commands which can still be taken back, most recent last. Copies of the state are only
+/// used to try things out, so a clone starts with nothing to undo rather than copying every delta.
+#[derive(Debug, Default)]
+pub struct UndoStack(Vec<Delta>);
+
+impl Clone for UndoStack {
+    fn clone(&self) -> Self {
+        UndoStack::default()
+    }
+}
+
+impl UndoStack {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn push(&mut self, delta: Delta) {
+        self.0.push(delta);
+    }
+
+    fn pop(&mut self) -> Option<Delta> {
+        self.0.pop()
+    }
+}
+
+/// The entities a command can change, so only those need copying before it runs. None means
+/// every entity of that kind, for commands whose reach depends on the state (eg. splash damage).
+struct Footprint {
+    units: Option<Vec<UnitId>>,
+    structures: Option<Vec<StructureId>>,
+    team_states: bool,
+    creep: bool,
+}
+
+impl Footprint {
+    fn of(command: &Command) -> Footprint {
+        let mut footprint = Footprint {
+            units: Some(vec![]),
+            structures: Some(vec![]),
+            team_states: true,
+            creep: false,
+        };
+        let transport_ids = |transport: &Transport| match *transport {
+            Transport::Unit(id) => (vec![id], vec![]),
+            Transport::Structure(id) => (vec![], vec![id]),
+        };
+
+        match command {
+            Command::Move { unit_id, .. } | Command::Wait { unit_id } => {
+                footprint.units = Some(vec![*unit_id]);
+                footprint.team_states = false;
+            }
+            Command::Attack { .. } | Command::AttackGround { .. } => {
+                footprint.units = None;
+                footprint.team_states = false;
+            }
+            Command::Research { structure_id, .. } | Command::Build { structure_id, .. } => {
+                footprint.structures = Some(vec![*structure_id]);
+            }
+            Command::Construct { worker_id, .. } => footprint.units = Some(vec![*worker_id]),
+            Command::Morph { unit_id, .. } => footprint.units = Some(vec![*unit_id]),
+            Command::Load { unit_id, transport }
+            | Command::Unload {
+                unit_id, transport, ..
+            } => {
+                let (mut units, structures) = transport_ids(transport);
+                units.push(*unit_id);
+                footprint.units = Some(units);
+                footprint.structures = Some(structures);
+            }
+            Command::Repair { worker_id, target } => match *target {
+                RepairTarget::Unit(id) => footprint.units = Some(vec![*worker_id, id]),
+                RepairTarget::Structure(id) => footprint.structures = Some(vec![id]),
+            },
+            Command::Heal {
+                healer_id,
+                target_id,
+            } => footprint.units = Some(vec![*healer_id, *target_id]),
+            Command::Act { .. } => {
+                footprint.units = None;
+                footprint.structures = None;
+            }
+            Command::EndTurn => {
+                footprint.units = None;
+                footprint.structures = None;
+                footprint.creep = true;
+            }
+        }
+        footprint
+    }
+}
+
+/// The parts of the state a command can change, copied before it runs.
+struct Before {
+    units: Snapshot<Unit>,
+    structures: Snapshot<Structure>,
+    team_states: Option<HashMap<TeamID, TeamState>>,
+    creep: Option<Creep>,
+    active_team: TeamID,
+    turn: u32,
+    rng: Rng,
+    visible_enemies: Vec<UnitId>,
+}
+
+impl Before {
+    fn take(state: &ScenarioState, command: &Command) -> Before {
+        let footprint = Footprint::of(command);
+        let team = state.active_team;
+        Before {
+            units: Snapshot::take(&state.units, |unit| unit.id, footprint.units.as_deref()),
+            structures: Snapshot::take(
+                &state.structures,
+                |structure| structure.id,
+                footprint.structures.as_deref(),
+            ),
+            team_states: footprint.team_states.then(|| state.team_states.clone()),
+            creep: footprint.creep.then(|| state.creep.clone()),
+            active_team: team,
+            turn: state.turn,
+            rng: state.rng,
+            visible_enemies: state
+                .units
+                .iter()
+                .filter(|unit| unit.team != team && state.is_visible_to(unit, team))
+                .map(|unit| unit.id)
+                .collect(),
+        }
+    }
+}
+
+impl Delta {
+    fn between(before: Before, after: &ScenarioState, result: &CommandResult) -> Delta {
+        let reveals_information = reveals_information(&before, after, result);
+        Delta {
+            units: ListDelta::between(before.units, &after.units, |unit| unit.id),
+            structures: ListDelta::between(before.structures, &after.structures, |structure| {
+                structure.id
+            }),
+            team_states: before
+                .team_states
+                .filter(|team_states| *team_states != after.team_states),
+            creep: before.creep.filter(|creep| *creep != after.creep),
+            active_team: before.active_team,
+            turn: before.turn,
+            rng: before.rng,
+            reveals_information,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+            && self.structures.is_empty()
+            && self.team_states.is_none()
+            && self.creep.is_none()
+    }
+}
+
+/// Whether the team that issued a command learned something from it. Luck counts, as does
+/// anything about the other teams' turns.
+fn reveals_information(before: &Before, after: &ScenarioState, result: &CommandResult) -> bool {
+    let team = before.active_team;
+    let ambushed = matches!(
+        result,
+        CommandResult::Move {
+            trapped_by: Some(_),
+            ..
+        } | CommandResult::Act {
+            trapped_by: Some(_),
+            ..
+        }
+    );
+    let spotted_enemy = after.units.iter().any(|unit| {
+        unit.team != team
+            && after.is_visible_to(unit, team)
+            && !before.visible_enemies.contains(&unit.id)
+    });
+
+    ambushed || spotted_enemy || before.rng != after.rng || before.active_team != after.active_team
+}
+
+impl ScenarioState {
+    /// Executes the command, also returning what would undo it.
+    pub fn execute_with_delta(&mut self, command: Command) -> (CommandResult, Delta) {
+        let before = Before::take(self, &command);
+        let result = self.apply(command);
+        let delta = Delta::between(before, self, &result);
+        (result, delta)
+    }
+
+    pub(crate) fn record(&mut self, delta: Delta) {
+        match self.rules.undo {
+            UndoPolicy::Never => {}
+            // Turns can't be taken back, so each one starts with nothing to undo.
+            _ if delta.active_team != self.active_team => self.undo_stack.clear(),
+            // Taking it back would let the player act on what they just learned.
+            UndoPolicy::BeforeReveal if delta.reveals_information => self.undo_stack.clear(),
+            _ if delta.is_empty() => {}
+            _ => self.undo_stack.push(delta),
+        }
+    }
+
+    /// Puts the state back to how it was before the command the delta came from.
+    pub fn revert(&mut self, delta: Delta) {
+        delta.units.revert(&mut self.units, |unit| unit.id);
+        delta
+            .structures
+            .revert(&mut self.structures, |structure| structure.id);
+        if let Some(team_states) = delta.team_states {
+            self.team_states = team_states;
+        }
+        if let Some(creep) = delta.creep {
+            self.creep = creep;
+        }
+        self.active_team = delta.active_team;
+        self.turn = delta.turn;
+        self.rng = delta.rng;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Takes back the last command, if the rules allow it. Returns whether anything was undone.
+    pub fn undo(&mut self) -> bool {
+        match self.undo_stack.pop() {
+            Some(delta) => {
+                self.revert(delta);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameRules, dev_helpers::new_scenario_state};
+
+    fn assert_same(a: &ScenarioState, b: &ScenarioState) {
+        assert_eq!(a.units, b.units);
+        assert_eq!(a.structures, b.structures);
+        assert_eq!(a.team_states, b.team_states);
+        assert_eq!(a.creep, b.creep);
+        assert_eq!(a.active_team, b.active_team);
+        assert_eq!(a.turn, b.turn);
+        assert_eq!(a.rng, b.rng);
+    }
+
+    #[test]
+    fn every_command_can_be_reverted() {
+        let rules = GameRules {
+            fog: true,
+            luck: 0.2,
+            ..Default::default()
+        };
+        let mut state = new_scenario_state(rules);
+        let mut picker = Rng::new(3);
+
+        for _ in 0..300 {
+            let commands = state.legal_commands(state.active_team);
+            let command = commands[picker.next_u64() as usize % commands.len()].clone();
+
+            let mut after = state.clone();
+            let (_, delta) = after.execute_with_delta(command.clone());
+            let mut reverted = after.clone();
+            reverted.revert(delta);
+
+            assert_same(&reverted, &state);
+            state = after;
+        }
+    }
+
+    #[test]
+    fn undo_is_limited_to_the_current_turn() {
+        let rules = GameRules {
+            undo: UndoPolicy::Always,
+            ..Default::default()
+        };
+        let mut state = new_scenario_state(rules);
+        let before = state.clone();
+        let command = state
+            .legal_commands_for_unit(0)
+            .into_iter()
+            .find(|command| matches!(command, Command::Move { .. }))
+            .unwrap();
+
+        state.execute(command);
+        assert!(state.undo());
+        assert_same(&state, &before);
+        assert!(!state.undo());
+
+        state.execute(Command::EndTurn);
+        assert!(!state.can_undo());
+    }
+
+    #[test]
+    fn luck_cannot_be_undone_before_reveal() {
+        let rules = GameRules {
+            undo: UndoPolicy::BeforeReveal,
+            luck: 0.2,
+            ..Default::default()
+        };
+        let mut state = new_scenario_state(rules);
+        let attack = state
+            .legal_commands(0)
+            .into_iter()
+            .find(|command| matches!(command, Command::Attack { .. }))
+            .unwrap();
+
+        state.execute(attack);
+        assert!(!state.can_undo());
+    }
+
+    #[test]
+    fn simulations_leave_the_undo_stack_behind() {
+        let rules = GameRules {
+            undo: UndoPolicy::Always,
+            ..Default::default()
+        };
+        let mut state = new_scenario_state(rules);
+        let before = state.clone();
+        for unit_id in [0, 1] {
+            let command = state
+                .legal_commands_for_unit(unit_id)
+                .into_iter()
+                .find(|command| matches!(command, Command::Move { .. }))
+                .unwrap();
+            state.execute(command);
+        }
+        assert_eq!(state.undo_stack.len(), 2);
+
+        // Neither copies nor legality checks carry the history along.
+        assert!(state.clone().undo_stack.is_empty());
+        let command = state.legal_commands(0).pop().unwrap();
+        assert!(state.is_legal(&command));
+        assert_eq!(state.undo_stack.len(), 2);
+
+        assert!(state.undo());
+        assert!(state.undo());
+        assert_same(&state, &before);
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{DamageTag, ScenarioState, TeamID, Unit, UnitId, UnitType};
+
+/// Air and ground units occupy tiles independently of each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Layer {
+    Ground,
+    Air,
+}
+
+impl Layer {
+    pub fn tag(&self) -> DamageTag {
+        match self {
+            Layer::Ground => DamageTag::Ground,
+            Layer::Air => DamageTag::Air,
+        }
+    }
+}
+
+impl UnitType {
+    pub fn layer(&self) -> Layer {
+        if self.value().tags.contains(&Some(DamageTag::Air)) {
+            Layer::Air
+        } else {
+            Layer::Ground
+        }
+    }
+}
+
+impl Unit {
+    pub fn layer(&self) -> Layer {
+        self.unit_type.layer()
+    }
+
+    pub fn uses_fuel(&self) -> bool {
+        self.unit_type.value().fuel_per_turn > 0.0
+    }
+}
+
+impl ScenarioState {
+    pub fn get_unit_at_layer(&self, x: u32, y: u32, layer: Layer) -> Option<&Unit> {
+        self.units
+            .iter()
+            .find(|unit| unit.position.x == x && unit.position.y == y && unit.layer() == layer)
+    }
+
+    /// Whether the attacker has a weapon which can hit the target's layer.
+    pub fn can_hit(&self, attacker: &Unit, target: &Unit) -> bool {
+        attacker
+            .unit_type
+            .value()
+            .weapon_one
+            .is_some_and(|weapon| weapon.can_target(target.layer()))
+    }
+
+    /// Burns fuel at the start of a team's turn. Units which run out crash and are removed.
+    pub(crate) fn burn_fuel(&mut self, team: TeamID) -> Vec<UnitId> {
+        let mut crashed = vec![];
+        for unit in self.units.iter_mut() {
+            if unit.team != team || !unit.uses_fuel() {
+                continue;
+            }
+            unit.fuel = (unit.fuel - unit.unit_type.value().fuel_per_turn).max(0.0);
+            if unit.fuel <= 0.0 {
+                crashed.push(unit.id);
+            }
+        }
+        self.units.retain(|unit| !crashed.contains(&unit.id));
+        crashed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, GameRules, dev_helpers::new_scenario_with_units};
+
+    /// A marine and a zergling facing a mutalisk and a roach.
+    fn state() -> ScenarioState {
+        new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::Marine, (2, 0), 0),
+                (UnitType::Zergling, (2, 1), 0),
+                (UnitType::Mutalisk, (3, 0), 1),
+                (UnitType::Roach, (3, 1), 1),
+            ],
+        )
+    }
+
+    #[test]
+    fn enemies_only_block_their_own_layer() {
+        let state = state();
+        assert!(state.is_tile_moveable(0, 3, 0));
+        assert!(!state.is_tile_moveable(0, 3, 1));
+        assert_eq!(state.get_unit_at_layer(3, 0, Layer::Air).unwrap().id, 2);
+        assert!(state.get_unit_at_layer(3, 0, Layer::Ground).is_none());
+    }
+
+    #[test]
+    fn weapons_only_hit_the_layers_they_target() {
+        let state = state();
+        let mutalisk = state.get_unit(2).unwrap();
+        assert!(state.can_hit(state.get_unit(0).unwrap(), mutalisk));
+        assert!(!state.can_hit(state.get_unit(1).unwrap(), mutalisk));
+    }
+
+    #[test]
+    fn air_units_crash_when_they_run_out_of_fuel() {
+        let mut state = state();
+        let mutalisk = state.units.iter_mut().find(|unit| unit.id == 2).unwrap();
+        mutalisk.fuel = UnitType::Mutalisk.value().fuel_per_turn * 2.0;
+
+        state.execute(Command::EndTurn);
+        assert!(state.get_unit(2).is_some());
+        state.execute(Command::EndTurn);
+        state.execute(Command::EndTurn);
+        assert!(state.get_unit(2).is_none());
+    }
+}
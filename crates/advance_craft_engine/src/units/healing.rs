@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CommandErr, CommandResult, CommandStatus, DamageTag, ScenarioState, StructureId, TeamID,
+    UnitHp, UnitId, structures::Race as StructureRace, units::Race,
+};
+
+/// Health regained by Zerg units at the start of their turn.
+pub const ZERG_REGENERATION: f32 = 3.0;
+pub const CREEP_REGENERATION: f32 = 10.0;
+/// Health regained by units next to a friendly structure at the start of their turn.
+pub const STRUCTURE_HEALING: f32 = 20.0;
+/// Repairs cost this fraction of the cost of the health restored.
+pub const REPAIR_COST_FRACTION: f32 = 0.25;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Support {
+    Repair(f32), // Mechanical units and Terran structures
+    Heal(f32),   // Biological units
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RepairTarget {
+    Unit(UnitId),
+    Structure(StructureId),
+}
+
+impl ScenarioState {
+    /// Regeneration and structure healing, applied at the start of a team's turn.
+    /// Structures also refuel the units next to them.
+    pub(crate) fn start_of_turn_healing(&mut self, team: TeamID) -> Vec<(UnitId, UnitHp)> {
+        let mut healing = vec![];
+        for unit in self.units.iter().filter(|unit| unit.team == team) {
+            let mut amount = 0.0;
+            if unit.unit_type.value().race == Race::Zerg {
+                amount += if self.has_creep(team, &unit.position) {
+                    CREEP_REGENERATION
+                } else {
+                    ZERG_REGENERATION
+                };
+            }
+
+            let next_to_structure = self.structures.iter().any(|structure| {
+                structure.team == team
+                    && structure.is_complete()
+                    && structure
+                        .tiles()
+                        .iter()
+                        .any(|tile| self.in_range(tile, &unit.position, 0.0, 1.0))
+            });
+            if next_to_structure {
+                amount += STRUCTURE_HEALING;
+            }
+
+            if amount > 0.0 {
+                healing.push((
+                    unit.id,
+                    amount,
+                    self.get_max_health(unit),
+                    next_to_structure,
+                ));
+            }
+        }
+
+        let mut unit_hp_changes = vec![];
+        for (unit_id, amount, max_health, resupply) in healing {
+            let unit = self.units.iter_mut().find(|u| u.id == unit_id).unwrap();
+            if resupply {
+                unit.fuel = unit.unit_type.value().max_fuel;
+            }
+            let new_health = (unit.health + amount).min(max_health);
+            if new_health > unit.health {
+                unit.health = new_health;
+                unit_hp_changes.push((unit_id, new_health));
+            }
+        }
+        unit_hp_changes
+    }
+
+    pub(crate) fn repair(&mut self, worker_id: UnitId, target: RepairTarget) -> CommandResult {
+        let worker = self.get_unit(worker_id).expect("No worker found");
+        let team = worker.team;
+        let funds = self.team_states.get(&team).map_or(0, |t| t.funds);
+
+        let error = |err: CommandErr| CommandResult::Repair {
+            status: CommandStatus::Err(err),
+            unit_hp_changes: vec![],
+            structure_hp_changes: vec![],
+            funds,
+        };
+
+        if team != self.active_team {
+            return error(CommandErr::WrongTeam);
+        }
+
+        if worker.has_attacked {
+            return error(CommandErr::AlreadyAttacked);
+        }
+
+        if !worker.can_move() {
+            return error(CommandErr::Incapacitated);
+        }
+
+        let Some(Support::Repair(repair_health)) = worker.unit_type.value().support else {
+            return error(CommandErr::MissingAbility);
+        };
+
+        // Target's team, tiles, health, max health and cost
+        let (target_team, tiles, health, max_health, cost) = match target {
+            RepairTarget::Unit(unit_id) => {
+                let Some(unit) = self.get_unit(unit_id) else {
+                    return error(CommandErr::InvalidTarget);
+                };
+                let stats = unit.unit_type.value();
+                if unit_id == worker_id || !stats.tags.contains(&Some(DamageTag::Mechanical)) {
+                    return error(CommandErr::InvalidTarget);
+                }
+                let max_health = self.get_max_health(unit);
+                (
+                    unit.team,
+                    vec![unit.position],
+                    unit.health,
+                    max_health,
+                    stats.cost,
+                )
+            }
+            RepairTarget::Structure(structure_id) => {
+                let Some(structure) = self.get_structure(structure_id) else {
+                    return error(CommandErr::InvalidTarget);
+                };
+                let stats = structure.structure_type.value();
+                if !structure.is_complete() || !matches!(stats.race, StructureRace::Terran) {
+                    return error(CommandErr::InvalidTarget);
+                }
+                let tiles = structure.tiles();
+                (
+                    structure.team,
+                    tiles,
+                    structure.health,
+                    stats.max_health,
+                    stats.cost,
+                )
+            }
+        };
+
+        if target_team != team {
+            return error(CommandErr::WrongTeam);
+        }
+
+        if !tiles
+            .iter()
+            .any(|tile| self.in_range(tile, &worker.position, 0.0, 1.0))
+        {
+            return error(CommandErr::OutOfRange);
+        }
+
+        if health >= max_health {
+            return error(CommandErr::FullHealth);
+        }
+
+        let restored = repair_health.min(max_health - health);
+        let repair_cost =
+            (cost as f32 * restored / max_health * REPAIR_COST_FRACTION).ceil() as u32;
+        if funds < repair_cost {
+            return error(CommandErr::InsufficientFunds);
+        }
+
+        let team_state = self.team_states.entry(team).or_default();
+        team_state.funds -= repair_cost;
+        let funds = team_state.funds;
+
+        let worker = self.units.iter_mut().find(|u| u.id == worker_id).unwrap();
+        worker.has_moved = true;
+        worker.has_attacked = true;
+
+        let mut unit_hp_changes = vec![];
+        let mut structure_hp_changes = vec![];
+        match target {
+            RepairTarget::Unit(unit_id) => {
+                let unit = self.units.iter_mut().find(|u| u.id == unit_id).unwrap();
+                unit.health += restored;
+                unit_hp_changes.push((unit_id, unit.health));
+            }
+            RepairTarget::Structure(structure_id) => {
+                let structure = self
+                    .structures
+                    .iter_mut()
+                    .find(|s| s.id == structure_id)
+                    .unwrap();
+                structure.health += restored;
+                structure_hp_changes.push((structure_id, structure.health));
+            }
+        }
+
+        CommandResult::Repair {
+            status: CommandStatus::Ok,
+            unit_hp_changes,
+            structure_hp_changes,
+            funds,
+        }
+    }
+
+    pub(crate) fn heal(&mut self, healer_id: UnitId, target_id: UnitId) -> CommandResult {
+        let error = |err: CommandErr| CommandResult::Heal {
+            status: CommandStatus::Err(err),
+            unit_hp_changes: vec![],
+        };
+
+        if healer_id == target_id {
+            return error(CommandErr::InvalidTarget);
+        }
+
+        let (healer, target) = self.get_two_units(healer_id, target_id).unwrap();
+
+        if healer.team != self.active_team || target.team != healer.team {
+            return error(CommandErr::WrongTeam);
+        }
+
+        if healer.has_attacked {
+            return error(CommandErr::AlreadyAttacked);
+        }
+
+        if !healer.can_move() {
+            return error(CommandErr::Incapacitated);
+        }
+
+        let Some(Support::Heal(heal_health)) = healer.unit_type.value().support else {
+            return error(CommandErr::MissingAbility);
+        };
+
+        if !target
+            .unit_type
+            .value()
+            .tags
+            .contains(&Some(DamageTag::Biological))
+        {
+            return error(CommandErr::InvalidTarget);
+        }
+
+        if !self.in_range(&healer.position, &target.position, 0.0, 1.0) {
+            return error(CommandErr::OutOfRange);
+        }
+
+        let max_health = self.get_max_health(target);
+        if target.health >= max_health {
+            return error(CommandErr::FullHealth);
+        }
+
+        let (healer, target) = self.get_two_units_mut(healer_id, target_id).unwrap();
+        healer.has_moved = true;
+        healer.has_attacked = true;
+        target.health = (target.health + heal_health).min(max_health);
+
+        CommandResult::Heal {
+            status: CommandStatus::Ok,
+            unit_hp_changes: vec![(target_id, target.health)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, GameRules, UnitType, dev_helpers::new_scenario_with_units};
+
+    #[test]
+    fn zerg_units_regenerate_at_the_start_of_their_turn() {
+        let mut state = new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::Marine, (5, 0), 0),
+                (UnitType::Roach, (0, 0), 1),
+                (UnitType::Roach, (6, 0), 1),
+            ],
+        );
+        state.units[1].health = 10.0;
+        state.units[2].health = 1.0;
+
+        let result = state.execute(Command::Attack {
+            attacker_id: 0,
+            defender_id: 2,
+        });
+        assert_eq!(result.status(), CommandStatus::Ok);
+        assert!(state.get_unit(2).is_none());
+
+        let CommandResult::EndTurn {
+            unit_hp_changes, ..
+        } = state.execute(Command::EndTurn)
+        else {
+            unreachable!();
+        };
+
+        assert_eq!(unit_hp_changes, vec![(1, 10.0 + ZERG_REGENERATION)]);
+        assert_eq!(state.get_unit(1).unwrap().health, 10.0 + ZERG_REGENERATION);
+        assert!(state.get_unit(2).is_none());
+    }
+}
@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ScenarioState, TeamID, TerrainType, Tile, Unit, UnitId};
+
+use super::{air::Layer, weapon::Delivery};
+
+/// How a weapon needs to see its target.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Sight {
+    Ignore,   // No line of sight rules, eg. melee
+    Direct,   // Blocked by mountains, high ground and structures
+    Indirect, // Fires over obstacles, but a friendly unit has to spot the target
+}
+
+/// Units can spot targets for indirect fire within this distance.
+pub const SPOTTING_RANGE: f32 = 3.0;
+
+/// Whether a unit can currently be attacked, and if not, why.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TargetStatus {
+    Hittable,
+    CannotTarget,
+    NotDetected,
+    OutOfRange,
+    NoLineOfSight,
+}
+
+impl TerrainType {
+    pub fn is_elevated(&self) -> bool {
+        matches!(self, TerrainType::Mountain | TerrainType::HighGround)
+    }
+
+    fn blocks_sight(&self, from_elevated: bool) -> bool {
+        match self {
+            TerrainType::Mountain => true,
+            TerrainType::HighGround => !from_elevated,
+            _ => false,
+        }
+    }
+}
+
+/// The tiles crossed by a straight line between two tiles, not including either end.
+fn tiles_between(from: &Tile, to: &Tile) -> Vec<Tile> {
+    let dx = to.x as f32 - from.x as f32;
+    let dy = to.y as f32 - from.y as f32;
+    let samples = (dx.abs().max(dy.abs()) * 4.0) as u32;
+
+    let mut tiles: Vec<Tile> = vec![];
+    for i in 1..samples {
+        let t = i as f32 / samples as f32;
+        let tile = Tile {
+            x: (from.x as f32 + 0.5 + dx * t).floor() as u32,
+            y: (from.y as f32 + 0.5 + dy * t).floor() as u32,
+        };
+        if tile != *from && tile != *to && !tiles.contains(&tile) {
+            tiles.push(tile);
+        }
+    }
+    tiles
+}
+
+impl ScenarioState {
+    fn terrain_at(&self, tile: &Tile) -> TerrainType {
+        self.map[tile.y as usize][tile.x as usize]
+    }
+
+    /// Whether direct fire from one tile can reach another on the ground.
+    pub fn has_line_of_sight(&self, from: &Tile, to: &Tile) -> bool {
+        let from_elevated = self.terrain_at(from).is_elevated();
+        if !from_elevated && self.terrain_at(to).is_elevated() {
+            return false;
+        }
+
+        tiles_between(from, to).iter().all(|tile| {
+            !self.terrain_at(tile).blocks_sight(from_elevated)
+                && self.get_structure_at(tile.x, tile.y).is_none()
+        })
+    }
+
+    /// Whether any unit on the team can see the tile well enough to direct indirect fire.
+    pub fn is_spotted(&self, team: TeamID, tile: &Tile) -> bool {
+        self.units.iter().any(|spotter| {
+            spotter.team == team
+                && self.distance(&spotter.position, tile) <= SPOTTING_RANGE
+                && (spotter.layer() == Layer::Air
+                    || self.has_line_of_sight(&spotter.position, tile))
+        })
+    }
+
+    /// Whether the attacker's weapon can see a target on the given tile and layer.
+    /// Nothing blocks sight to or from the air.
+    pub fn has_sight_of(&self, attacker: &Unit, tile: &Tile, layer: Layer) -> bool {
+        if attacker.layer() == Layer::Air || layer == Layer::Air {
+            return true;
+        }
+
+        let Some(weapon) = attacker.unit_type.value().weapon_one else {
+            return false;
+        };
+        match weapon.sight {
+            Sight::Ignore => true,
+            Sight::Direct => self.has_line_of_sight(&attacker.position, tile),
+            Sight::Indirect => self.is_spotted(attacker.team, tile),
+        }
+    }
+
+    pub fn target_status(&self, attacker_id: UnitId, defender_id: UnitId) -> TargetStatus {
+        let (Some(attacker), Some(defender)) =
+            (self.get_unit(attacker_id), self.get_unit(defender_id))
+        else {
+            return TargetStatus::CannotTarget;
+        };
+
+        if attacker_id == defender_id || !self.can_hit(attacker, defender) {
+            return TargetStatus::CannotTarget;
+        }
+
+        // Splash weapons fire at the tile, so they can still catch undetected units.
+        let weapon = attacker.unit_type.value().weapon_one.unwrap();
+        if !matches!(weapon.delivery, Delivery::Splash(_))
+            && defender.requires_detection()
+            && !self.is_detected(defender, attacker.team)
+        {
+            return TargetStatus::NotDetected;
+        }
+
+        if !self.check_range(attacker, defender) {
+            return TargetStatus::OutOfRange;
+        }
+
+        if !self.has_sight_of(attacker, &defender.position, defender.layer()) {
+            return TargetStatus::NoLineOfSight;
+        }
+
+        TargetStatus::Hittable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameRules, UnitType, dev_helpers::new_scenario_with_units};
+
+    /// The target status of a roach firing at a marine, on the dev map with its mountain at
+    /// (3, 2), high ground at (6, 1) and (7, 1) and a command centre at (1, 2).
+    fn roach_at_marine(roach: (u32, u32), marine: (u32, u32)) -> TargetStatus {
+        let state = new_scenario_with_units(
+            GameRules::default(),
+            &[(UnitType::Roach, roach, 1), (UnitType::Marine, marine, 0)],
+        );
+        state.target_status(0, 1)
+    }
+
+    #[test]
+    fn lines_skip_both_ends() {
+        let tiles = tiles_between(&Tile { x: 0, y: 0 }, &Tile { x: 3, y: 0 });
+        assert_eq!(tiles, vec![Tile { x: 1, y: 0 }, Tile { x: 2, y: 0 }]);
+    }
+
+    #[test]
+    fn mountains_and_structures_block_direct_fire() {
+        assert_eq!(roach_at_marine((4, 3), (4, 1)), TargetStatus::Hittable);
+        assert_eq!(roach_at_marine((3, 3), (3, 1)), TargetStatus::NoLineOfSight);
+        assert_eq!(roach_at_marine((1, 3), (1, 1)), TargetStatus::NoLineOfSight);
+    }
+
+    #[test]
+    fn only_high_ground_sees_high_ground() {
+        assert_eq!(roach_at_marine((5, 1), (7, 1)), TargetStatus::NoLineOfSight);
+        assert_eq!(roach_at_marine((6, 1), (4, 1)), TargetStatus::Hittable);
+    }
+
+    #[test]
+    fn indirect_fire_needs_a_spotter() {
+        let mut state = new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::SiegeTank, (3, 0), 0),
+                (UnitType::Roach, (3, 3), 1),
+            ],
+        );
+        assert_eq!(state.target_status(0, 1), TargetStatus::NoLineOfSight);
+
+        state
+            .units
+            .push(Unit::new(2, UnitType::Marine, Tile { x: 4, y: 3 }, 0));
+        assert!(state.is_spotted(0, &Tile { x: 3, y: 3 }));
+        assert_eq!(state.target_status(0, 1), TargetStatus::Hittable);
+    }
+}
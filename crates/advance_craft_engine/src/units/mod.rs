@@ -1,4 +1,18 @@
+pub mod air;
+pub mod healing;
+pub mod line_of_sight;
+pub mod morph;
+pub mod shields;
+pub mod transport;
 pub mod units;
+pub mod veterancy;
 pub mod weapon;
 
+pub use air::*;
+pub use healing::*;
+pub use line_of_sight::*;
+pub use morph::*;
+pub use shields::*;
+pub use transport::*;
 pub use units::*;
+pub use veterancy::*;
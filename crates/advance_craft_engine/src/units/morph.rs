@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CommandErr, CommandResult, CommandStatus, ScenarioState, StatusEffect, StatusEffectType,
+    TeamID, UnitId, UnitType,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MorphInProgress {
+    pub into: UnitType,
+    pub turns_remaining: u32,
+}
+
+impl ScenarioState {
+    pub(crate) fn morph(&mut self, unit_id: UnitId, into: UnitType) -> CommandResult {
+        let unit = self.get_unit(unit_id).expect("No unit found");
+        let team = unit.team;
+        let funds = self.team_states.get(&team).map_or(0, |t| t.funds);
+
+        let error = |err: CommandErr| CommandResult::Morph {
+            status: CommandStatus::Err(err),
+            unit_id,
+            into,
+            funds,
+        };
+
+        if team != self.active_team {
+            return error(CommandErr::WrongTeam);
+        }
+
+        if unit.has_attacked || !unit.can_move() {
+            return error(CommandErr::Incapacitated);
+        }
+
+        let Some(morph) = unit.unit_type.get_morph(into) else {
+            return error(CommandErr::CannotProduce);
+        };
+
+        if !self
+            .missing_requirements(team, &morph.requirements)
+            .is_empty()
+        {
+            return error(CommandErr::RequirementsNotMet);
+        }
+
+        if funds < morph.cost {
+            return error(CommandErr::InsufficientFunds);
+        }
+
+        let extra_supply = into
+            .value()
+            .supply_cost
+            .saturating_sub(unit.unit_type.value().supply_cost);
+        if !self.has_supply_for(team, extra_supply) {
+            return error(CommandErr::SupplyBlocked);
+        }
+
+        let team_state = self.team_states.entry(team).or_default();
+        team_state.funds -= morph.cost;
+        let funds = team_state.funds;
+
+        let unit = self.units.iter_mut().find(|u| u.id == unit_id).unwrap();
+        unit.morph = Some(MorphInProgress {
+            into,
+            turns_remaining: morph.turns,
+        });
+        unit.apply_status_effect(StatusEffect::permanent(StatusEffectType::Cocooned));
+        unit.has_moved = true;
+        unit.has_attacked = true;
+
+        CommandResult::Morph {
+            status: CommandStatus::Ok,
+            unit_id,
+            into,
+            funds,
+        }
+    }
+
+    pub(crate) fn progress_morphs(&mut self, team: TeamID) -> Vec<(UnitId, UnitType)> {
+        let mut completed = vec![];
+        for unit in self.units.iter_mut() {
+            if unit.team != team {
+                continue;
+            }
+            let Some(morph) = unit.morph.as_mut() else {
+                continue;
+            };
+
+            morph.turns_remaining = morph.turns_remaining.saturating_sub(1);
+            if morph.turns_remaining == 0 {
+                completed.push((unit.id, morph.into));
+            }
+        }
+
+        for (unit_id, into) in completed.iter() {
+            // Keep the same proportion of health through the morph, upgrades included.
+            let unit = self.get_unit(*unit_id).unwrap();
+            let mut morphed = unit.clone();
+            morphed.unit_type = *into;
+            morphed.health =
+                unit.health / self.get_max_health(unit) * self.get_max_health(&morphed);
+            morphed.morph = None;
+            morphed.remove_status_effect(StatusEffectType::Cocooned);
+
+            let unit = self.units.iter_mut().find(|u| u.id == *unit_id).unwrap();
+            *unit = morphed;
+        }
+        completed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, GameRules, dev_helpers::new_scenario_with_units};
+
+    fn morph_roach(health: f32) -> ScenarioState {
+        let mut state = new_scenario_with_units(
+            GameRules::default(),
+            &[(UnitType::Marine, (8, 5), 0), (UnitType::Roach, (5, 0), 1)],
+        );
+        state.active_team = 1;
+        let roach = &mut state.units[1];
+        roach.health = health;
+        roach.apply_status_effect(StatusEffect::permanent(StatusEffectType::Cocooned));
+        roach.morph = Some(MorphInProgress {
+            into: UnitType::Ravager,
+            turns_remaining: 1,
+        });
+
+        state.execute(Command::EndTurn);
+        state
+    }
+
+    #[test]
+    fn morphing_keeps_the_proportion_of_health() {
+        let roach = UnitType::Roach.value().max_health;
+        let state = morph_roach(roach / 2.0);
+
+        let ravager = state.get_unit(1).unwrap();
+        assert_eq!(ravager.unit_type, UnitType::Ravager);
+        assert_eq!(ravager.health, state.get_max_health(ravager) / 2.0);
+        assert!(ravager.can_move());
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ScenarioState, TeamID, Unit};
+
+/// Damage split between a unit's shields and its health.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Damage {
+    pub shields: f32,
+    pub health: f32,
+}
+
+impl Damage {
+    pub fn total(&self) -> f32 {
+        self.shields + self.health
+    }
+}
+
+impl Unit {
+    pub fn max_shields(&self) -> f32 {
+        self.unit_type.value().max_shields
+    }
+
+    pub fn take_damage(&mut self, damage: &Damage) {
+        self.shields = (self.shields - damage.shields).max(0.0);
+        self.health -= damage.health;
+        if damage.total() > 0.0 {
+            self.recently_damaged = true;
+        }
+    }
+}
+
+impl ScenarioState {
+    /// Shields recharge fully at the start of a team's turn, unless the unit
+    /// has taken damage since the start of its previous turn.
+    pub(crate) fn regenerate_shields(&mut self, team: TeamID) {
+        for unit in self.units.iter_mut().filter(|unit| unit.team == team) {
+            if !unit.recently_damaged {
+                unit.shields = unit.max_shields();
+            }
+            unit.recently_damaged = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Command, CommandStatus, GameRules, UnitType, dev_helpers::new_scenario_with_units,
+    };
+
+    /// A marine next to a zealot.
+    fn state() -> ScenarioState {
+        new_scenario_with_units(
+            GameRules::default(),
+            &[(UnitType::Marine, (2, 0), 0), (UnitType::Zealot, (3, 0), 1)],
+        )
+    }
+
+    #[test]
+    fn shields_absorb_damage_before_health() {
+        let mut state = state();
+        let zealot = state.get_unit(1).unwrap().clone();
+
+        let result = state.execute(Command::Attack {
+            attacker_id: 0,
+            defender_id: 1,
+        });
+        assert_eq!(result.status(), CommandStatus::Ok);
+
+        let damaged = state.get_unit(1).unwrap();
+        assert_eq!(damaged.health, zealot.health);
+        assert!(damaged.shields < zealot.shields);
+        assert!(damaged.recently_damaged);
+    }
+
+    #[test]
+    fn leftover_damage_goes_to_health() {
+        let mut zealot = state().get_unit(1).unwrap().clone();
+        zealot.shields = 5.0;
+
+        zealot.take_damage(&Damage {
+            shields: 5.0,
+            health: 3.0,
+        });
+        assert_eq!(zealot.shields, 0.0);
+        assert_eq!(zealot.health, UnitType::Zealot.value().max_health - 3.0);
+    }
+
+    #[test]
+    fn recently_damaged_units_wait_a_turn_to_recharge() {
+        let mut state = state();
+        let zealot = state.units.iter_mut().find(|unit| unit.id == 1).unwrap();
+        zealot.shields = 10.0;
+        zealot.recently_damaged = true;
+
+        state.regenerate_shields(1);
+        let zealot = state.get_unit(1).unwrap();
+        assert_eq!(zealot.shields, 10.0);
+        assert!(!zealot.recently_damaged);
+
+        state.regenerate_shields(1);
+        assert_eq!(
+            state.get_unit(1).unwrap().shields,
+            UnitType::Zealot.value().max_shields
+        );
+    }
+
+    #[test]
+    fn shields_recharge_at_the_start_of_their_own_turn() {
+        let mut state = state();
+        let zealot = state.units.iter_mut().find(|unit| unit.id == 1).unwrap();
+        zealot.shields = 10.0;
+
+        state.execute(Command::EndTurn);
+        assert_eq!(state.active_team, 1);
+        assert_eq!(
+            state.get_unit(1).unwrap().shields,
+            UnitType::Zealot.value().max_shields
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CommandErr, CommandResult, CommandStatus, ScenarioState, StructureId, TeamID, TerrainType,
+    Tile, Unit, UnitId,
+};
+
+use super::air::Layer;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Transport {
+    Unit(UnitId),
+    // Structures share their cargo with every structure of the same type, eg. a Nydus Network.
+    Structure(StructureId),
+}
+
+impl Unit {
+    pub fn cargo_used(&self) -> u32 {
+        self.cargo
+            .iter()
+            .map(|unit| unit.unit_type.value().cargo_size)
+            .sum()
+    }
+}
+
+struct TransportInfo {
+    team: TeamID,
+    position: Tile,
+    capacity: u32,
+}
+
+impl ScenarioState {
+    fn transport_info(&self, transport: Transport) -> Option<TransportInfo> {
+        match transport {
+            Transport::Unit(unit_id) => self.get_unit(unit_id).map(|unit| TransportInfo {
+                team: unit.team,
+                position: unit.position,
+                capacity: unit.unit_type.value().cargo_capacity,
+            }),
+            Transport::Structure(structure_id) => self
+                .get_structure(structure_id)
+                .filter(|structure| structure.is_complete())
+                .map(|structure| TransportInfo {
+                    team: structure.team,
+                    position: structure.position,
+                    capacity: structure.structure_type.value().cargo_capacity,
+                }),
+        }
+    }
+
+    pub fn get_cargo(&self, transport: Transport) -> &[Unit] {
+        match transport {
+            Transport::Unit(unit_id) => self.get_unit(unit_id).map_or(&[], |unit| &unit.cargo),
+            Transport::Structure(structure_id) => self
+                .get_structure(structure_id)
+                .and_then(|structure| self.team_states.get(&structure.team))
+                .map_or(&[], |team_state| &team_state.nydus_network),
+        }
+    }
+
+    fn get_cargo_mut(&mut self, transport: Transport) -> Option<&mut Vec<Unit>> {
+        match transport {
+            Transport::Unit(unit_id) => self
+                .units
+                .iter_mut()
+                .find(|unit| unit.id == unit_id)
+                .map(|unit| &mut unit.cargo),
+            Transport::Structure(structure_id) => {
+                let team = self.get_structure(structure_id)?.team;
+                Some(&mut self.team_states.entry(team).or_default().nydus_network)
+            }
+        }
+    }
+
+    /// Whether the unit is being carried by a transport, rather than being on the map.
+    pub fn is_carried(&self, unit_id: UnitId) -> bool {
+        let carried_by_unit = self
+            .units
+            .iter()
+            .any(|unit| unit.cargo.iter().any(|cargo| cargo.id == unit_id));
+        let in_network = self.team_states.values().any(|team_state| {
+            team_state
+                .nydus_network
+                .iter()
+                .any(|cargo| cargo.id == unit_id)
+        });
+        carried_by_unit || in_network
+    }
+
+    pub(crate) fn load(&mut self, unit_id: UnitId, transport: Transport) -> CommandResult {
+        let error = |err: CommandErr| CommandResult::Load {
+            status: CommandStatus::Err(err),
+            unit_id,
+            transport,
+        };
+
+        let unit = self.get_unit(unit_id).expect("No unit found");
+        let Some(info) = self.transport_info(transport) else {
+            return error(CommandErr::InvalidTarget);
+        };
+
+        if unit.team != self.active_team || info.team != unit.team {
+            return error(CommandErr::WrongTeam);
+        }
+
+        if unit.has_moved {
+            return error(CommandErr::AlreadyMoved);
+        }
+
+        if !unit.can_move() {
+            return error(CommandErr::Incapacitated);
+        }
+
+        let cargo_size = unit.unit_type.value().cargo_size;
+        if cargo_size == 0 || transport == Transport::Unit(unit_id) {
+            return error(CommandErr::InvalidTarget);
+        }
+
+        if !self.in_range(&unit.position, &info.position, 0.0, 1.0) {
+            return error(CommandErr::OutOfRange);
+        }
+
+        let cargo_used: u32 = self
+            .get_cargo(transport)
+            .iter()
+            .map(|cargo| cargo.unit_type.value().cargo_size)
+            .sum();
+        if cargo_used + cargo_size > info.capacity {
+            return error(CommandErr::CargoFull);
+        }
+
+        let index = self.units.iter().position(|u| u.id == unit_id).unwrap();
+        let mut unit = self.units.remove(index);
+        unit.has_moved = true;
+        unit.has_attacked = true;
+        self.get_cargo_mut(transport).unwrap().push(unit);
+
+        CommandResult::Load {
+            status: CommandStatus::Ok,
+            unit_id,
+            transport,
+        }
+    }
+
+    pub(crate) fn unload(
+        &mut self,
+        transport: Transport,
+        unit_id: UnitId,
+        tile: Tile,
+    ) -> CommandResult {
+        let error = |err: CommandErr| CommandResult::Unload {
+            status: CommandStatus::Err(err),
+            unit: None,
+            transport,
+        };
+
+        let Some(info) = self.transport_info(transport) else {
+            return error(CommandErr::InvalidTarget);
+        };
+
+        if info.team != self.active_team {
+            return error(CommandErr::WrongTeam);
+        }
+
+        if let Transport::Unit(transport_id) = transport {
+            let transport_unit = self.get_unit(transport_id).unwrap();
+            if !transport_unit.can_move() {
+                return error(CommandErr::Incapacitated);
+            }
+        }
+
+        let Some(unit) = self
+            .get_cargo(transport)
+            .iter()
+            .find(|cargo| cargo.id == unit_id)
+        else {
+            return error(CommandErr::InvalidTarget);
+        };
+
+        if !self.in_range(&tile, &info.position, 0.0, 1.0) {
+            return error(CommandErr::OutOfRange);
+        }
+
+        if self
+            .is_tile_within_bounds(tile.x as i32, tile.y as i32)
+            .is_err()
+        {
+            return error(CommandErr::InvalidPlacement);
+        }
+
+        let terrain = self.map[tile.y as usize][tile.x as usize];
+        if unit.layer() == Layer::Ground && matches!(terrain, TerrainType::Water) {
+            return error(CommandErr::InvalidPlacement);
+        }
+
+        if self
+            .get_unit_at_layer(tile.x, tile.y, unit.layer())
+            .is_some()
+        {
+            return error(CommandErr::TileOccupied);
+        }
+
+        let cargo = self.get_cargo_mut(transport).unwrap();
+        let index = cargo.iter().position(|u| u.id == unit_id).unwrap();
+        let mut unit = cargo.remove(index);
+        unit.position = tile;
+        // Unloaded units can't act until their next turn.
+        unit.has_moved = true;
+        unit.has_attacked = true;
+        self.units.push(unit.clone());
+
+        CommandResult::Unload {
+            status: CommandStatus::Ok,
+            unit: Some(unit),
+            transport,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, GameRules, UnitType, dev_helpers::new_scenario_with_units};
+
+    const MEDIVAC: Transport = Transport::Unit(1);
+
+    /// A marine next to a medivac.
+    fn state() -> ScenarioState {
+        new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::Marine, (2, 0), 0),
+                (UnitType::Medivac, (3, 0), 0),
+                (UnitType::Marine, (6, 0), 0),
+            ],
+        )
+    }
+
+    fn load(state: &mut ScenarioState, unit_id: UnitId) -> CommandStatus {
+        state
+            .execute(Command::Load {
+                unit_id,
+                transport: MEDIVAC,
+            })
+            .status()
+    }
+
+    #[test]
+    fn loaded_units_leave_the_map_until_unloaded() {
+        let mut state = state();
+        assert_eq!(load(&mut state, 0), CommandStatus::Ok);
+        assert!(state.get_unit(0).is_none());
+        assert!(state.is_carried(0));
+        assert_eq!(state.get_cargo(MEDIVAC).len(), 1);
+
+        state.execute(Command::EndTurn);
+        state.execute(Command::EndTurn);
+        let result = state.execute(Command::Unload {
+            transport: MEDIVAC,
+            unit_id: 0,
+            tile: Tile { x: 4, y: 0 },
+        });
+        assert_eq!(result.status(), CommandStatus::Ok);
+        assert!(!state.is_carried(0));
+        let marine = state.get_unit(0).unwrap();
+        assert_eq!(marine.position, Tile { x: 4, y: 0 });
+        assert!(marine.has_moved);
+    }
+
+    #[test]
+    fn new_units_never_reuse_a_carried_units_id() {
+        let mut state = new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::Medivac, (3, 0), 0),
+                (UnitType::Marine, (2, 0), 0),
+            ],
+        );
+        let status = state
+            .execute(Command::Load {
+                unit_id: 1,
+                transport: Transport::Unit(0),
+            })
+            .status();
+        assert_eq!(status, CommandStatus::Ok);
+        assert_eq!(state.next_unit_id(), 2);
+    }
+
+    #[test]
+    fn units_must_be_next_to_the_transport() {
+        let mut state = state();
+        assert_eq!(
+            load(&mut state, 2),
+            CommandStatus::Err(CommandErr::OutOfRange)
+        );
+
+        load(&mut state, 0);
+        let result = state.execute(Command::Unload {
+            transport: MEDIVAC,
+            unit_id: 0,
+            tile: Tile { x: 5, y: 0 },
+        });
+        assert_eq!(result.status(), CommandStatus::Err(CommandErr::OutOfRange));
+        assert!(state.is_carried(0));
+    }
+
+    #[test]
+    fn transports_have_limited_capacity() {
+        let mut state = state();
+        let marine = state.get_unit(2).unwrap().clone();
+        let capacity = UnitType::Medivac.value().cargo_capacity;
+        let cargo = state.get_cargo_mut(MEDIVAC).unwrap();
+        for id in 0..capacity {
+            cargo.push(Unit {
+                id: 100 + id,
+                ..marine.clone()
+            });
+        }
+
+        assert_eq!(
+            load(&mut state, 0),
+            CommandStatus::Err(CommandErr::CargoFull)
+        );
+        assert!(state.get_unit(0).is_some());
+    }
+}
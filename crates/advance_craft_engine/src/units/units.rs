@@ -1,12 +1,25 @@
+use super::healing::Support;
 use super::weapon::*;
+use crate::{Requirement, Requirements, StructureType};
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum UnitType {
     Zergling,
     Roach,
     Marine,
     Baneling,
     SiegeTank,
+    Scv,
+    Drone,
+    Ravager,
+    Probe,
+    Zealot,
+    Stalker,
+    Immortal,
+    Mutalisk,
+    Viking,
+    Medivac,
+    Overlord,
 }
 
 #[derive(PartialEq, Serialize, Deserialize)]
@@ -17,7 +30,7 @@ pub enum DamageTag {
     Armoured,
     Ground,
     Structure,
-    _Air,
+    Air,
     _Infantry,
 }
 
@@ -27,22 +40,52 @@ pub struct UnitStats {
     pub name: &'static str,
     pub race: Race,
     pub max_health: f32,
+    pub max_shields: f32,
     pub max_ammo: f32,
     pub max_fuel: f32,
+    pub fuel_per_turn: f32,
     pub tags: [Option<DamageTag>; 4],
     pub base_armour: f32,
     pub weapon_one: Option<Weapon>,
     pub weapon_two: Option<Weapon>,
+    pub detection_range: Option<f32>,
+    pub cost: u32,
+    pub produced_at: Option<StructureType>,
+    pub requirements: Requirements,
+    pub cargo_capacity: u32,
+    pub cargo_size: u32, // 0 if the unit can't be transported
+    pub supply_cost: u32,
+    pub supply_provided: u32,
+    pub support: Option<Support>,
 }
 
 #[derive(PartialEq, Serialize, Deserialize)]
 pub enum Race {
     Zerg,
     Terran,
-    _Protoss,
+    Protoss,
 }
 
 impl UnitType {
+    pub const ALL: [UnitType; 16] = [
+        UnitType::Scv,
+        UnitType::Marine,
+        UnitType::SiegeTank,
+        UnitType::Drone,
+        UnitType::Zergling,
+        UnitType::Baneling,
+        UnitType::Roach,
+        UnitType::Ravager,
+        UnitType::Probe,
+        UnitType::Zealot,
+        UnitType::Stalker,
+        UnitType::Immortal,
+        UnitType::Mutalisk,
+        UnitType::Viking,
+        UnitType::Medivac,
+        UnitType::Overlord,
+    ];
+
     pub const fn value(&self) -> UnitStats {
         use UnitType::*;
         match *self {
@@ -51,10 +94,65 @@ impl UnitType {
             Roach => ROACH,
             Marine => MARINE,
             SiegeTank => SIEGE_TANK,
+            Scv => SCV,
+            Drone => DRONE,
+            Ravager => RAVAGER,
+            Probe => PROBE,
+            Zealot => ZEALOT,
+            Stalker => STALKER,
+            Immortal => IMMORTAL,
+            Mutalisk => MUTALISK,
+            Viking => VIKING,
+            Medivac => MEDIVAC,
+            Overlord => OVERLORD,
+        }
+    }
+
+    pub const fn morphs(&self) -> [Option<Morph>; 2] {
+        use UnitType::*;
+        match *self {
+            Zergling => [Some(ZERGLING_TO_BANELING), None],
+            Roach => [Some(ROACH_TO_RAVAGER), None],
+            _ => [None, None],
         }
     }
+
+    pub fn get_morph(&self, into: UnitType) -> Option<Morph> {
+        self.morphs()
+            .into_iter()
+            .flatten()
+            .find(|morph| morph.into == into)
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Morph {
+    pub into: UnitType,
+    pub cost: u32,
+    pub turns: u32,
+    pub requirements: Requirements,
+}
+
+pub const ZERGLING_TO_BANELING: Morph = Morph {
+    into: UnitType::Baneling,
+    cost: 25,
+    turns: 1,
+    requirements: [
+        Some(Requirement::Structure(StructureType::BanelingNest)),
+        None,
+    ],
+};
+
+pub const ROACH_TO_RAVAGER: Morph = Morph {
+    into: UnitType::Ravager,
+    cost: 100,
+    turns: 2,
+    requirements: [
+        Some(Requirement::Structure(StructureType::RoachWarren)),
+        None,
+    ],
+};
+
 use DamageTag::*;
 use serde::{Deserialize, Serialize};
 
@@ -63,12 +161,26 @@ pub const ZERGLING: UnitStats = UnitStats {
     race: Race::Zerg,
     name: "Zergling",
     max_health: 35.0,
+    max_shields: 0.0,
     max_ammo: -1.0,
     max_fuel: -1.0,
+    fuel_per_turn: 0.0,
     tags: [Some(Ground), Some(Light), Some(Biological), None],
     base_armour: 0.0,
     weapon_one: Some(ZERGLING_ATTACK),
     weapon_two: None,
+    detection_range: None,
+    cost: 25,
+    produced_at: Some(StructureType::Hatchery),
+    requirements: [
+        Some(Requirement::Structure(StructureType::SpawningPool)),
+        None,
+    ],
+    cargo_capacity: 0,
+    cargo_size: 1,
+    supply_cost: 1,
+    supply_provided: 0,
+    support: None,
 };
 
 pub const BANELING: UnitStats = UnitStats {
@@ -76,12 +188,23 @@ pub const BANELING: UnitStats = UnitStats {
     race: Race::Zerg,
     name: "Baneling",
     max_health: 30.0,
+    max_shields: 0.0,
     max_ammo: -1.0,
     max_fuel: -1.0,
+    fuel_per_turn: 0.0,
     tags: [Some(Ground), Some(Light), Some(Biological), None],
     base_armour: 0.0,
     weapon_one: Some(BANELING_ATTACK),
     weapon_two: None,
+    detection_range: None,
+    cost: 50,
+    produced_at: None,
+    requirements: [None, None],
+    cargo_capacity: 0,
+    cargo_size: 1,
+    supply_cost: 1,
+    supply_provided: 0,
+    support: None,
 };
 
 pub const MARINE: UnitStats = UnitStats {
@@ -89,12 +212,23 @@ pub const MARINE: UnitStats = UnitStats {
     race: Race::Terran,
     name: "Marine",
     max_health: 55.0,
+    max_shields: 0.0,
     max_ammo: -1.0,
     max_fuel: -1.0,
+    fuel_per_turn: 0.0,
     tags: [Some(Ground), Some(Light), Some(Biological), None],
     base_armour: 0.0,
     weapon_one: Some(MARINE_ATTACK),
     weapon_two: None,
+    detection_range: None,
+    cost: 50,
+    produced_at: Some(StructureType::Barracks),
+    requirements: [None, None],
+    cargo_capacity: 0,
+    cargo_size: 1,
+    supply_cost: 1,
+    supply_provided: 0,
+    support: None,
 };
 
 pub const SIEGE_TANK: UnitStats = UnitStats {
@@ -102,12 +236,23 @@ pub const SIEGE_TANK: UnitStats = UnitStats {
     race: Race::Terran,
     name: "Siege Tank",
     max_health: 175.0,
+    max_shields: 0.0,
     max_ammo: 8.0,
     max_fuel: -1.0,
+    fuel_per_turn: 0.0,
     tags: [Some(Ground), Some(Armoured), Some(Mechanical), None],
     base_armour: 1.0,
     weapon_one: Some(SIEGED_ATTACK),
     weapon_two: None,
+    detection_range: None,
+    cost: 275,
+    produced_at: Some(StructureType::Factory),
+    requirements: [None, None],
+    cargo_capacity: 0,
+    cargo_size: 4,
+    supply_cost: 3,
+    supply_provided: 0,
+    support: None,
     // weapon_one: Some(UNSIEGED_ATTACK),
     // weapon_two: Some(SIEGED_ATTACK),
 };
@@ -117,13 +262,299 @@ pub const ROACH: UnitStats = UnitStats {
     race: Race::Zerg,
     name: "Roach",
     max_health: 145.0,
+    max_shields: 0.0,
     max_ammo: -1.0,
     max_fuel: -1.0,
+    fuel_per_turn: 0.0,
     tags: [Some(Ground), Some(Armoured), Some(Biological), None],
     base_armour: 1.0,
     weapon_one: Some(ROACH_ATTACK),
     weapon_two: None,
+    detection_range: None,
+    cost: 100,
+    produced_at: Some(StructureType::Hatchery),
+    requirements: [
+        Some(Requirement::Structure(StructureType::RoachWarren)),
+        None,
+    ],
+    cargo_capacity: 0,
+    cargo_size: 2,
+    supply_cost: 2,
+    supply_provided: 0,
+    support: None,
+};
+pub const RAVAGER: UnitStats = UnitStats {
+    id: 8,
+    race: Race::Zerg,
+    name: "Ravager",
+    max_health: 120.0,
+    max_shields: 0.0,
+    max_ammo: -1.0,
+    max_fuel: -1.0,
+    fuel_per_turn: 0.0,
+    tags: [Some(Ground), Some(Biological), None, None],
+    base_armour: 1.0,
+    weapon_one: Some(RAVAGER_ATTACK),
+    weapon_two: None,
+    detection_range: None,
+    cost: 200,
+    produced_at: None,
+    requirements: [None, None],
+    cargo_capacity: 0,
+    cargo_size: 4,
+    supply_cost: 3,
+    supply_provided: 0,
+    support: None,
+};
+
+pub const SCV: UnitStats = UnitStats {
+    id: 6,
+    race: Race::Terran,
+    name: "SCV",
+    max_health: 45.0,
+    max_shields: 0.0,
+    max_ammo: -1.0,
+    max_fuel: -1.0,
+    fuel_per_turn: 0.0,
+    tags: [
+        Some(Ground),
+        Some(Light),
+        Some(Biological),
+        Some(Mechanical),
+    ],
+    base_armour: 0.0,
+    weapon_one: Some(SCV_ATTACK),
+    weapon_two: None,
+    detection_range: None,
+    cost: 50,
+    produced_at: Some(StructureType::CommandCentre),
+    requirements: [None, None],
+    cargo_capacity: 0,
+    cargo_size: 1,
+    supply_cost: 1,
+    supply_provided: 0,
+    support: Some(Support::Repair(25.0)),
+};
+
+pub const DRONE: UnitStats = UnitStats {
+    id: 7,
+    race: Race::Zerg,
+    name: "Drone",
+    max_health: 40.0,
+    max_shields: 0.0,
+    max_ammo: -1.0,
+    max_fuel: -1.0,
+    fuel_per_turn: 0.0,
+    tags: [Some(Ground), Some(Light), Some(Biological), None],
+    base_armour: 0.0,
+    weapon_one: Some(DRONE_ATTACK),
+    weapon_two: None,
+    detection_range: None,
+    cost: 50,
+    produced_at: Some(StructureType::Hatchery),
+    requirements: [None, None],
+    cargo_capacity: 0,
+    cargo_size: 1,
+    supply_cost: 1,
+    supply_provided: 0,
+    support: None,
+};
+
+pub const PROBE: UnitStats = UnitStats {
+    id: 9,
+    race: Race::Protoss,
+    name: "Probe",
+    max_health: 20.0,
+    max_shields: 20.0,
+    max_ammo: -1.0,
+    max_fuel: -1.0,
+    fuel_per_turn: 0.0,
+    tags: [Some(Ground), Some(Light), Some(Mechanical), None],
+    base_armour: 0.0,
+    weapon_one: Some(PROBE_ATTACK),
+    weapon_two: None,
+    detection_range: None,
+    cost: 50,
+    produced_at: Some(StructureType::Nexus),
+    requirements: [None, None],
+    cargo_capacity: 0,
+    cargo_size: 1,
+    supply_cost: 1,
+    supply_provided: 0,
+    support: None,
+};
+
+pub const ZEALOT: UnitStats = UnitStats {
+    id: 10,
+    race: Race::Protoss,
+    name: "Zealot",
+    max_health: 100.0,
+    max_shields: 50.0,
+    max_ammo: -1.0,
+    max_fuel: -1.0,
+    fuel_per_turn: 0.0,
+    tags: [Some(Ground), Some(Light), Some(Biological), None],
+    base_armour: 1.0,
+    weapon_one: Some(ZEALOT_ATTACK),
+    weapon_two: None,
+    detection_range: None,
+    cost: 100,
+    produced_at: Some(StructureType::Gateway),
+    requirements: [None, None],
+    cargo_capacity: 0,
+    cargo_size: 2,
+    supply_cost: 2,
+    supply_provided: 0,
+    support: None,
+};
+
+pub const STALKER: UnitStats = UnitStats {
+    id: 11,
+    race: Race::Protoss,
+    name: "Stalker",
+    max_health: 80.0,
+    max_shields: 80.0,
+    max_ammo: -1.0,
+    max_fuel: -1.0,
+    fuel_per_turn: 0.0,
+    tags: [Some(Ground), Some(Armoured), Some(Mechanical), None],
+    base_armour: 1.0,
+    weapon_one: Some(STALKER_ATTACK),
+    weapon_two: None,
+    detection_range: None,
+    cost: 175,
+    produced_at: Some(StructureType::Gateway),
+    requirements: [
+        Some(Requirement::Structure(StructureType::CyberneticsCore)),
+        None,
+    ],
+    cargo_capacity: 0,
+    cargo_size: 2,
+    supply_cost: 2,
+    supply_provided: 0,
+    support: None,
+};
+
+pub const IMMORTAL: UnitStats = UnitStats {
+    id: 12,
+    race: Race::Protoss,
+    name: "Immortal",
+    max_health: 200.0,
+    max_shields: 100.0,
+    max_ammo: -1.0,
+    max_fuel: -1.0,
+    fuel_per_turn: 0.0,
+    tags: [Some(Ground), Some(Armoured), Some(Mechanical), None],
+    base_armour: 1.0,
+    weapon_one: Some(IMMORTAL_ATTACK),
+    weapon_two: None,
+    detection_range: None,
+    cost: 375,
+    produced_at: Some(StructureType::RoboticsFacility),
+    requirements: [None, None],
+    cargo_capacity: 0,
+    cargo_size: 4,
+    supply_cost: 4,
+    supply_provided: 0,
+    support: None,
+};
+
+pub const MUTALISK: UnitStats = UnitStats {
+    id: 13,
+    race: Race::Zerg,
+    name: "Mutalisk",
+    max_health: 120.0,
+    max_shields: 0.0,
+    max_ammo: -1.0,
+    max_fuel: 60.0,
+    fuel_per_turn: 5.0,
+    tags: [Some(Air), Some(Light), Some(Biological), None],
+    base_armour: 0.0,
+    weapon_one: Some(MUTALISK_ATTACK),
+    weapon_two: None,
+    detection_range: None,
+    cost: 200,
+    produced_at: Some(StructureType::Hatchery),
+    requirements: [Some(Requirement::Structure(StructureType::Spire)), None],
+    cargo_capacity: 0,
+    cargo_size: 0,
+    supply_cost: 2,
+    supply_provided: 0,
+    support: None,
+};
+
+pub const VIKING: UnitStats = UnitStats {
+    id: 14,
+    race: Race::Terran,
+    name: "Viking",
+    max_health: 135.0,
+    max_shields: 0.0,
+    max_ammo: -1.0,
+    max_fuel: 99.0,
+    fuel_per_turn: 5.0,
+    tags: [Some(Air), Some(Armoured), Some(Mechanical), None],
+    base_armour: 0.0,
+    weapon_one: Some(VIKING_ATTACK),
+    weapon_two: None,
+    detection_range: None,
+    cost: 225,
+    produced_at: Some(StructureType::Starport),
+    requirements: [None, None],
+    cargo_capacity: 0,
+    cargo_size: 0,
+    supply_cost: 2,
+    supply_provided: 0,
+    support: None,
+};
+
+pub const MEDIVAC: UnitStats = UnitStats {
+    id: 15,
+    race: Race::Terran,
+    name: "Medivac",
+    max_health: 150.0,
+    max_shields: 0.0,
+    max_ammo: -1.0,
+    max_fuel: 99.0,
+    fuel_per_turn: 2.0,
+    tags: [Some(Air), Some(Armoured), Some(Mechanical), None],
+    base_armour: 1.0,
+    weapon_one: None,
+    weapon_two: None,
+    detection_range: None,
+    cost: 200,
+    produced_at: Some(StructureType::Starport),
+    requirements: [None, None],
+    cargo_capacity: 8,
+    cargo_size: 0,
+    supply_cost: 2,
+    supply_provided: 0,
+    support: Some(Support::Heal(20.0)),
 };
+
+pub const OVERLORD: UnitStats = UnitStats {
+    id: 16,
+    race: Race::Zerg,
+    name: "Overlord",
+    max_health: 200.0,
+    max_shields: 0.0,
+    max_ammo: -1.0,
+    max_fuel: -1.0,
+    fuel_per_turn: 0.0,
+    tags: [Some(Air), Some(Armoured), Some(Biological), None],
+    base_armour: 0.0,
+    weapon_one: None,
+    weapon_two: None,
+    detection_range: Some(3.0),
+    cost: 100,
+    produced_at: Some(StructureType::Hatchery),
+    requirements: [None, None],
+    cargo_capacity: 8,
+    cargo_size: 0,
+    supply_cost: 0,
+    supply_provided: 8,
+    support: None,
+};
+
 //    Unit     Range     Speed
 // Marine      5 => 2    3.2 => 3
 // Marauder    5 => 2    3.2 => 3
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Damage, Unit};
+
+/// Bonus experience for destroying an enemy, on top of the damage dealt.
+pub const KILL_EXPERIENCE: f32 = 25.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Rank {
+    #[default]
+    Rookie,
+    One,
+    Two,
+    Veteran,
+}
+
+pub struct RankStats {
+    pub name: &'static str,
+    pub experience: f32, // Needed to reach this rank
+    pub damage_multiplier: f32,
+    pub defence_multiplier: f32, // Damage taken is divided by this
+}
+
+impl Rank {
+    pub const ALL: [Rank; 4] = [Rank::Rookie, Rank::One, Rank::Two, Rank::Veteran];
+
+    pub const fn value(&self) -> RankStats {
+        use Rank::*;
+        match *self {
+            Rookie => ROOKIE,
+            One => RANK_ONE,
+            Two => RANK_TWO,
+            Veteran => VETERAN,
+        }
+    }
+
+    pub fn from_experience(experience: f32) -> Rank {
+        Rank::ALL
+            .into_iter()
+            .filter(|rank| experience >= rank.value().experience)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+pub const ROOKIE: RankStats = RankStats {
+    name: "Rookie",
+    experience: 0.0,
+    damage_multiplier: 1.0,
+    defence_multiplier: 1.0,
+};
+
+pub const RANK_ONE: RankStats = RankStats {
+    name: "I",
+    experience: 40.0,
+    damage_multiplier: 1.1,
+    defence_multiplier: 1.0,
+};
+
+pub const RANK_TWO: RankStats = RankStats {
+    name: "II",
+    experience: 100.0,
+    damage_multiplier: 1.2,
+    defence_multiplier: 1.1,
+};
+
+pub const VETERAN: RankStats = RankStats {
+    name: "V",
+    experience: 200.0,
+    damage_multiplier: 1.3,
+    defence_multiplier: 1.2,
+};
+
+impl Unit {
+    /// Returns the new rank if the unit was promoted.
+    pub fn gain_experience(&mut self, experience: f32) -> Option<Rank> {
+        self.experience += experience;
+        let rank = Rank::from_experience(self.experience);
+        if rank > self.rank {
+            self.rank = rank;
+            Some(rank)
+        } else {
+            None
+        }
+    }
+}
+
+/// Only pass `target_destroyed` for the hit that destroyed the target, not for hitting a wreck.
+pub fn experience_for(damage: &Damage, target_destroyed: bool) -> f32 {
+    let kill_bonus = if target_destroyed {
+        KILL_EXPERIENCE
+    } else {
+        0.0
+    };
+    damage.total() + kill_bonus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Command, GameRules, Tile, UnitType, dev_helpers::new_scenario_with_units};
+
+    #[test]
+    fn kill_experience_is_only_paid_for_the_killing_blow() {
+        let mut state = new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::SiegeTank, (0, 0), 0),
+                (UnitType::Roach, (3, 0), 1),
+                (UnitType::Marine, (3, 1), 1),
+            ],
+        );
+        state.units[2].health = 0.0;
+        let expected =
+            state.calculate_damage(0, 1).1.total() + state.calculate_damage(0, 2).1.total();
+
+        state.execute(Command::AttackGround {
+            attacker_id: 0,
+            tile: Tile { x: 3, y: 0 },
+        });
+
+        assert_eq!(state.get_unit(0).unwrap().experience, expected);
+    }
+
+    #[test]
+    fn destroying_a_unit_pays_the_kill_bonus() {
+        let mut state = new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::SiegeTank, (0, 0), 0),
+                (UnitType::Marine, (3, 0), 1),
+            ],
+        );
+        state.units[1].health = 1.0;
+        let expected = state.calculate_damage(0, 1).1.total() + KILL_EXPERIENCE;
+
+        state.execute(Command::AttackGround {
+            attacker_id: 0,
+            tile: Tile { x: 3, y: 0 },
+        });
+
+        assert_eq!(state.get_unit(0).unwrap().experience, expected);
+    }
+}
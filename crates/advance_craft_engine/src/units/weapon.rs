@@ -1,6 +1,12 @@
 use serde::{Deserialize, Serialize};
 
-use super::units::{DamageTag, DamageTag::*};
+use crate::Unit;
+
+use super::{
+    air::Layer,
+    line_of_sight::Sight,
+    units::{DamageTag, DamageTag::*},
+};
 
 #[derive(PartialEq, Serialize, Deserialize)]
 pub struct Bonus {
@@ -33,29 +39,48 @@ pub struct Weapon {
     pub _id: usize,
     pub _name: &'static str,
     pub delivery: Delivery,
+    pub sight: Sight,
     pub base_damage: f32,
     pub _num_of_attacks: u32,
     pub bonuses: [Option<Bonus>; 4],
-    pub _applicable: [Option<DamageTag>; 4],
+    pub applicable: [Option<DamageTag>; 4],
     pub additional_effects: [Option<AdditionalEffect>; 4],
+    pub can_counter: bool,     // Indirect fire never fights back
+    pub fire_after_move: bool, // Indirect fire has to stay put to shoot
 }
 
 impl Weapon {
+    pub fn can_target(&self, layer: Layer) -> bool {
+        self.applicable.contains(&Some(layer.tag()))
+    }
+
     pub fn has_effect(&self, additional_effect: &AdditionalEffect) -> bool {
         self.additional_effects
             .contains(&Some(additional_effect.clone()))
     }
 }
 
+impl Unit {
+    pub fn can_counter(&self) -> bool {
+        self.unit_type
+            .value()
+            .weapon_one
+            .is_some_and(|weapon| weapon.can_counter)
+    }
+}
+
 pub const ZERGLING_ATTACK: Weapon = Weapon {
     _id: 1,
     _name: "Zergling Claws",
     delivery: Delivery::Melee,
+    sight: Sight::Ignore,
     base_damage: 10.0,
     bonuses: [None, None, None, None],
     _num_of_attacks: 1,
-    _applicable: [Some(Ground), None, None, None],
+    applicable: [Some(Ground), None, None, None],
     additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
 };
 
 pub const BANELING_ATTACK: Weapon = Weapon {
@@ -66,6 +91,7 @@ pub const BANELING_ATTACK: Weapon = Weapon {
         radius: 1.5,
         friendly: false,
     }),
+    sight: Sight::Ignore,
     base_damage: 16.0,
     bonuses: [
         Some(Bonus {
@@ -77,30 +103,38 @@ pub const BANELING_ATTACK: Weapon = Weapon {
         None,
     ],
     _num_of_attacks: 1,
-    _applicable: [Some(Ground), None, None, None],
+    applicable: [Some(Ground), None, None, None],
     additional_effects: [Some(AdditionalEffect::Suicide), None, None, None],
+    can_counter: false,
+    fire_after_move: true,
 };
 
 pub const MARINE_ATTACK: Weapon = Weapon {
     _id: 0,
     _name: "Machine Gun",
     delivery: Delivery::Melee,
+    sight: Sight::Ignore,
     base_damage: 9.8,
     bonuses: [None, None, None, None],
     _num_of_attacks: 1,
-    _applicable: [Some(Ground), None, None, None],
+    applicable: [Some(Ground), Some(Air), None, None],
     additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
 };
 
 pub const ROACH_ATTACK: Weapon = Weapon {
     _id: 3,
     _name: "Acid Saliva",
     delivery: Delivery::Ranged(1.0, 2.0),
+    sight: Sight::Direct,
     base_damage: 11.2,
     bonuses: [None, None, None, None],
     _num_of_attacks: 1,
-    _applicable: [Some(Ground), None, None, None],
+    applicable: [Some(Ground), None, None, None],
     additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
 };
 
 pub const SIEGED_ATTACK: Weapon = Weapon {
@@ -111,6 +145,7 @@ pub const SIEGED_ATTACK: Weapon = Weapon {
         radius: 1.5,
         friendly: true,
     }),
+    sight: Sight::Indirect,
     base_damage: 18.9,
     bonuses: [
         Some(Bonus {
@@ -122,6 +157,209 @@ pub const SIEGED_ATTACK: Weapon = Weapon {
         None,
     ],
     _num_of_attacks: 1,
-    _applicable: [Some(Ground), None, None, None],
+    applicable: [Some(Ground), None, None, None],
+    additional_effects: [None, None, None, None],
+    can_counter: false,
+    fire_after_move: false,
+};
+
+pub const SCV_ATTACK: Weapon = Weapon {
+    _id: 5,
+    _name: "Fusion Cutter",
+    delivery: Delivery::Melee,
+    sight: Sight::Ignore,
+    base_damage: 5.0,
+    bonuses: [None, None, None, None],
+    _num_of_attacks: 1,
+    applicable: [Some(Ground), None, None, None],
+    additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
+};
+
+pub const DRONE_ATTACK: Weapon = Weapon {
+    _id: 6,
+    _name: "Spinning Claws",
+    delivery: Delivery::Melee,
+    sight: Sight::Ignore,
+    base_damage: 5.0,
+    bonuses: [None, None, None, None],
+    _num_of_attacks: 1,
+    applicable: [Some(Ground), None, None, None],
+    additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
+};
+
+pub const RAVAGER_ATTACK: Weapon = Weapon {
+    _id: 7,
+    _name: "Ravager Plasma",
+    delivery: Delivery::Ranged(1.0, 3.0),
+    sight: Sight::Direct,
+    base_damage: 16.0,
+    bonuses: [None, None, None, None],
+    _num_of_attacks: 1,
+    applicable: [Some(Ground), None, None, None],
+    additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
+};
+
+pub const PROBE_ATTACK: Weapon = Weapon {
+    _id: 8,
+    _name: "Particle Beam",
+    delivery: Delivery::Melee,
+    sight: Sight::Ignore,
+    base_damage: 5.0,
+    bonuses: [None, None, None, None],
+    _num_of_attacks: 1,
+    applicable: [Some(Ground), None, None, None],
+    additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
+};
+
+pub const ZEALOT_ATTACK: Weapon = Weapon {
+    _id: 9,
+    _name: "Psi Blades",
+    delivery: Delivery::Melee,
+    sight: Sight::Ignore,
+    base_damage: 16.0,
+    bonuses: [None, None, None, None],
+    _num_of_attacks: 1,
+    applicable: [Some(Ground), None, None, None],
+    additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
+};
+
+pub const STALKER_ATTACK: Weapon = Weapon {
+    _id: 10,
+    _name: "Particle Disruptors",
+    delivery: Delivery::Ranged(1.0, 2.0),
+    sight: Sight::Direct,
+    base_damage: 13.0,
+    bonuses: [
+        Some(Bonus {
+            tag: Armoured,
+            additional_damage: 5.0,
+        }),
+        None,
+        None,
+        None,
+    ],
+    _num_of_attacks: 1,
+    applicable: [Some(Ground), Some(Air), None, None],
+    additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
+};
+
+pub const IMMORTAL_ATTACK: Weapon = Weapon {
+    _id: 11,
+    _name: "Phase Disruptors",
+    delivery: Delivery::Ranged(1.0, 2.0),
+    sight: Sight::Direct,
+    base_damage: 20.0,
+    bonuses: [
+        Some(Bonus {
+            tag: Armoured,
+            additional_damage: 30.0,
+        }),
+        None,
+        None,
+        None,
+    ],
+    _num_of_attacks: 1,
+    applicable: [Some(Ground), None, None, None],
+    additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
+};
+
+pub const MUTALISK_ATTACK: Weapon = Weapon {
+    _id: 12,
+    _name: "Glaive Wurm",
+    delivery: Delivery::Ranged(1.0, 2.0),
+    sight: Sight::Direct,
+    base_damage: 9.0,
+    bonuses: [None, None, None, None],
+    _num_of_attacks: 1,
+    applicable: [Some(Ground), Some(Air), None, None],
+    additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
+};
+
+pub const VIKING_ATTACK: Weapon = Weapon {
+    _id: 13,
+    _name: "Lanzer Torpedoes",
+    delivery: Delivery::Ranged(1.0, 3.0),
+    sight: Sight::Direct,
+    base_damage: 10.0,
+    bonuses: [
+        Some(Bonus {
+            tag: Armoured,
+            additional_damage: 4.0,
+        }),
+        None,
+        None,
+        None,
+    ],
+    _num_of_attacks: 1,
+    applicable: [Some(Air), None, None, None],
     additional_effects: [None, None, None, None],
+    can_counter: true,
+    fire_after_move: true,
 };
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Command, CommandErr, CommandStatus, GameRules, ScenarioState, Tile, UnitAction, UnitType,
+        dev_helpers::new_scenario_with_units,
+    };
+
+    /// A siege tank two tiles from a roach.
+    fn state() -> ScenarioState {
+        new_scenario_with_units(
+            GameRules::default(),
+            &[
+                (UnitType::SiegeTank, (4, 0), 0),
+                (UnitType::Roach, (4, 2), 1),
+            ],
+        )
+    }
+
+    #[test]
+    fn indirect_fire_cannot_follow_a_move() {
+        let mut state = state();
+        state.execute(Command::Move {
+            unit_id: 0,
+            tiles: vec![Tile { x: 5, y: 0 }],
+        });
+        let actions = state.get_possible_actions(&0);
+        assert!(
+            !actions
+                .iter()
+                .any(|action| matches!(action, UnitAction::Attack))
+        );
+
+        let result = state.execute(Command::AttackGround {
+            attacker_id: 0,
+            tile: Tile { x: 4, y: 2 },
+        });
+        assert_eq!(
+            result.status(),
+            CommandStatus::Err(CommandErr::CannotFireAfterMove)
+        );
+    }
+
+    #[test]
+    fn indirect_fire_does_not_counter_attack() {
+        let state = state();
+        assert!(!state.get_unit(0).unwrap().can_counter());
+        assert_eq!(state.calculate_damage(1, 0).0.total(), 0.0);
+        assert!(state.get_unit(1).unwrap().can_counter());
+    }
+}
@@ -1,6 +1,9 @@
 use std::net::SocketAddr;
 
-use advance_craft_engine::{Command, CommandResult, ScenarioState, TeamID};
+use advance_craft_ai::Difficulty;
+use advance_craft_engine::{
+    Command, CommandResult, ScenarioState, TeamID, replay::Replay, rules::GameRules,
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -11,7 +14,9 @@ pub type PlayerID = SocketAddr; // Temporary
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum ClientToServer {
     CreateGame {
-        // Map, rules etc.
+        // Map etc.
+        #[serde(default)]
+        rules: GameRules,
     },
     ConnectToGame {
         game_id: GameID,
@@ -21,6 +26,18 @@ pub enum ClientToServer {
         game_id: GameID,
         command: Command,
     },
+    AddAiPlayer {
+        game_id: GameID,
+        team_id: TeamID,
+        difficulty: Difficulty,
+    },
+    GetReplay {
+        game_id: GameID,
+    },
+    // Asks for a fresh copy of the game after the local one stopped matching the server's.
+    RequestResync {
+        game_id: GameID,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -37,18 +54,94 @@ pub enum ServerToClient {
         scenario_state: ScenarioState,
         team_id: TeamID,
     },
+    AddAiPlayerResult {
+        game_id: GameID,
+        team_id: TeamID,
+    },
+    ReplayResult {
+        game_id: GameID,
+        replay: Box<Replay>,
+    },
     // Combine these two?
     CommandResult {
         game_id: GameID,
         result: CommandResult,
+        state_hash: u64, // Of the receiving team's view after the command, so not the real RNG
     },
     GameUpdate {
         game_id: GameID,
         result: CommandResult,
+        state_hash: u64, // As for CommandResult
+    },
+    Resync {
+        game_id: GameID,
+        scenario_state: ScenarioState,
     },
 }
 impl ServerToClient {
     pub fn new_error(message: String) -> ServerToClient {
         ServerToClient::Error { message }
     }
+
+    /// Checks a client's copy of the game against the hash sent with a result, once the result
+    /// has been applied. If they differ, returns the message asking for a resync.
+    pub fn check_sync(&self, local_state: &ScenarioState) -> Option<ClientToServer> {
+        match self {
+            ServerToClient::CommandResult {
+                game_id,
+                state_hash,
+                ..
+            }
+            | ServerToClient::GameUpdate {
+                game_id,
+                state_hash,
+                ..
+            } if *state_hash != local_state.state_hash() => {
+                Some(ClientToServer::RequestResync { game_id: *game_id })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use advance_craft_engine::{dev_helpers::new_scenario_state, units::UnitType};
+
+    use super::*;
+
+    fn update(game_id: GameID, local_state: &mut ScenarioState) -> ServerToClient {
+        let result = local_state.execute(Command::EndTurn);
+        ServerToClient::GameUpdate {
+            game_id,
+            result,
+            state_hash: local_state.view_for_team(0).state_hash(),
+        }
+    }
+
+    #[test]
+    fn matching_states_stay_in_sync() {
+        let mut server_state = new_scenario_state(GameRules::default());
+        let message = update(Uuid::new_v4(), &mut server_state);
+
+        let local_state = server_state.view_for_team(0);
+        assert!(message.check_sync(&local_state).is_none());
+    }
+
+    #[test]
+    fn tampered_states_request_a_resync() {
+        let game_id = Uuid::new_v4();
+        let mut server_state = new_scenario_state(GameRules::default());
+        let message = update(game_id, &mut server_state);
+
+        let mut local_state = server_state.view_for_team(0);
+        local_state.units[0].unit_type = UnitType::Zergling;
+        assert!(matches!(
+            message.check_sync(&local_state),
+            Some(ClientToServer::RequestResync { game_id: id }) if id == game_id
+        ));
+
+        let error = ServerToClient::new_error("Not a result".to_string());
+        assert!(error.check_sync(&local_state).is_none());
+    }
 }
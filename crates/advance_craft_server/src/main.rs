@@ -1,13 +1,18 @@
 use std::{
     collections::HashMap,
-    env,
+    env, fs,
     io::Error as IoError,
     net::SocketAddr,
+    path::Path,
     sync::{Arc, Mutex},
 };
 
+use advance_craft_ai::{Difficulty, GreedyAgent, plan_turn};
 use advance_craft_engine::{
-    Command, CommandResult, ScenarioState, TeamID, dev_helpers::new_scenario_state,
+    Command, CommandResult, ScenarioState, TeamID,
+    dev_helpers::new_scenario_state,
+    replay::Replay,
+    rules::{GameOutcome, GameRules},
 };
 use advance_craft_server::*;
 use futures_channel::mpsc::{TrySendError, UnboundedSender, unbounded};
@@ -23,6 +28,8 @@ type PeerMap = Arc<Mutex<HashMap<SocketAddr, Tx>>>;
 type GameMap = Arc<Mutex<HashMap<GameID, Game>>>;
 type PlayerMap = Arc<Mutex<HashMap<PlayerID, Player>>>;
 
+const REPLAY_DIR: &str = "replays";
+
 struct Player {
     id: PlayerID,
     socket_addr: SocketAddr,
@@ -36,18 +43,53 @@ struct Game {
     players: Vec<(PlayerID, TeamID)>,
     started: bool,
     completed: bool,
-    history: Vec<Command>,
+    replay: Replay,
+    ai_players: Vec<(TeamID, Difficulty)>,
 }
 
 impl Game {
     pub fn new(scenario_state: ScenarioState) -> Game {
         Game {
             id: Uuid::new_v4(),
+            replay: Replay::new(&scenario_state),
             scenario_state,
             players: vec![],
             started: false,
             completed: false,
-            history: vec![],
+            ai_players: vec![],
+        }
+    }
+
+    fn execute(&mut self, command: Command) -> CommandResult {
+        let result = self.scenario_state.execute(command.clone());
+        self.replay.record(command, result.clone());
+        self.check_completed();
+        result
+    }
+
+    /// The hash of what the team can see, to compare with the team's own copy of the game. The
+    /// view's random number generator is redacted, so the real one is never checked this way.
+    fn state_hash_for(&self, team: TeamID) -> u64 {
+        self.scenario_state.view_for_team(team).state_hash()
+    }
+
+    fn check_completed(&mut self) {
+        if self.completed || self.scenario_state.outcome() == GameOutcome::Ongoing {
+            return;
+        }
+        self.completed = true;
+        self.save_replay();
+    }
+
+    fn save_replay(&self) {
+        let path = Path::new(REPLAY_DIR).join(format!("{}.json", self.id));
+        let saved = fs::create_dir_all(REPLAY_DIR).and_then(|_| {
+            let json = serde_json::to_string(&self.replay)?;
+            fs::write(&path, json)
+        });
+        match saved {
+            Ok(_) => println!("Saved replay to {}", path.display()),
+            Err(err) => println!("Failed to save replay for game {}: {:?}", self.id, err),
         }
     }
 }
@@ -123,13 +165,22 @@ fn handle_incoming(
 ) -> future::Ready<Result<(), tokio_tungstenite::tungstenite::Error>> {
     log_message(addr, &msg);
     let outgoing_message = match parse_incoming_message(&msg) {
-        Ok(ClientToServer::CreateGame {}) => handle_create_game(&game_map),
+        Ok(ClientToServer::CreateGame { rules }) => handle_create_game(&game_map, rules),
         Ok(ClientToServer::ConnectToGame { game_id, team_id }) => {
             handle_connect_to_game(&game_map, &game_id, addr, team_id)
         }
         Ok(ClientToServer::InGameCommand { game_id, command }) => {
             handle_game_command(&game_map, &game_id, command, addr)
         }
+        Ok(ClientToServer::AddAiPlayer {
+            game_id,
+            team_id,
+            difficulty,
+        }) => handle_add_ai_player(&game_map, &game_id, team_id, difficulty),
+        Ok(ClientToServer::GetReplay { game_id }) => handle_get_replay(&game_map, &game_id),
+        Ok(ClientToServer::RequestResync { game_id }) => {
+            handle_request_resync(&game_map, &game_id, addr)
+        }
         Err(err) => {
             println!("{:?}", err);
             ServerToClient::Error {
@@ -140,12 +191,93 @@ fn handle_incoming(
     };
 
     send_response(&outgoing_message, addr, peer_map).unwrap();
-    if let ServerToClient::CommandResult { result, game_id } = outgoing_message {
-        update_other_players(addr, &result, game_map, &game_id, peer_map);
+    match outgoing_message {
+        ServerToClient::CommandResult {
+            result, game_id, ..
+        } => {
+            update_other_players(addr, &result, game_map, &game_id, peer_map);
+            play_ai_turns(game_map, &game_id, peer_map);
+        }
+        ServerToClient::AddAiPlayerResult { game_id, .. } => {
+            play_ai_turns(game_map, &game_id, peer_map);
+        }
+        _ => {}
     }
     future::ok(())
 }
 
+/// Plays the turns belonging to AI players until it's a human's turn again, sending each
+/// result to the human players. At most one round is played per call, so a game left to the
+/// AIs can't keep the connection busy forever.
+fn play_ai_turns(game_map: &GameMap, game_id: &GameID, peer_map: &PeerMap) {
+    let Some(teams) = game_map
+        .lock()
+        .unwrap()
+        .get(game_id)
+        .map(|game| game.scenario_state.teams.len())
+    else {
+        return;
+    };
+
+    for _ in 0..teams {
+        let Some(messages) = play_ai_turn(game_map, game_id) else {
+            return;
+        };
+        for (player_id, message) in messages {
+            send_response(&message, &player_id, peer_map).unwrap();
+        }
+    }
+}
+
+/// Plays the active team's turn if it belongs to an AI, returning the updates for the human
+/// players. The lock is released while the AI thinks, so other games carry on meanwhile.
+fn play_ai_turn(game_map: &GameMap, game_id: &GameID) -> Option<Vec<(PlayerID, ServerToClient)>> {
+    let (scenario_state, difficulty) = {
+        let binding = game_map.lock().unwrap();
+        let game = binding.get(game_id)?;
+        let active_team = game.scenario_state.active_team;
+        let (_, difficulty) = game
+            .ai_players
+            .iter()
+            .find(|(team_id, _)| *team_id == active_team)?;
+        if game.completed {
+            return None;
+        }
+        (game.scenario_state.clone(), *difficulty)
+    };
+
+    let active_team = scenario_state.active_team;
+    let seed = scenario_state.rules.seed ^ scenario_state.turn as u64;
+    let mut agent = GreedyAgent::new(difficulty, seed);
+    let commands = plan_turn(&mut agent, &scenario_state);
+
+    let mut binding = game_map.lock().unwrap();
+    let game = binding.get_mut(game_id)?;
+    // The turn was played some other way while the AI was thinking.
+    if game.scenario_state.turn != scenario_state.turn
+        || game.scenario_state.active_team != active_team
+    {
+        return None;
+    }
+
+    let mut messages = vec![];
+    for command in commands {
+        if game.completed || game.scenario_state.active_team != active_team {
+            break;
+        }
+        let result = game.execute(command);
+        for (player_id, team_id) in game.players.iter() {
+            let message = ServerToClient::GameUpdate {
+                game_id: *game_id,
+                result: result.clone(),
+                state_hash: game.state_hash_for(*team_id),
+            };
+            messages.push((*player_id, message));
+        }
+    }
+    Some(messages)
+}
+
 fn update_other_players(
     issuing_player: &PlayerID,
     command_result: &CommandResult,
@@ -155,20 +287,21 @@ fn update_other_players(
 ) {
     let binding = game_map.lock().unwrap();
     let game = binding.get(game_id).unwrap();
-    for (player_id, _) in game.players.iter() {
+    for (player_id, team_id) in game.players.iter() {
         if player_id == issuing_player {
             continue;
         }
         let message = ServerToClient::GameUpdate {
             game_id: game_id.clone(),
             result: command_result.clone(),
+            state_hash: game.state_hash_for(*team_id),
         };
         send_response(&message, player_id, peer_map).unwrap();
     }
 }
 
-fn handle_create_game(game_map: &GameMap) -> ServerToClient {
-    let scenario_state = new_scenario_state();
+fn handle_create_game(game_map: &GameMap, rules: GameRules) -> ServerToClient {
+    let scenario_state = new_scenario_state(rules);
     let game = Game::new(scenario_state);
 
     game_map
@@ -197,23 +330,93 @@ fn handle_game_command(
     // TODO
     // Check if game is active
     // Check if it is the player's turn
-    let (_, issuing_team) = game
+    let (_, issuing_team) = *game
         .players
         .iter()
         .find(|(player_id, _)| player_id == issuing_player)
         .unwrap();
-    if game.scenario_state.active_team != *issuing_team {
+    if game.scenario_state.active_team != issuing_team {
         return ServerToClient::new_error("Not your turn".to_string());
     }
 
+    if game.completed {
+        return ServerToClient::new_error("Game is over".to_string());
+    }
+
     if !game.started {
         game.started = true;
     }
 
-    let result = game.scenario_state.execute(command);
+    let result = game.execute(command);
     return ServerToClient::CommandResult {
         game_id: game.id,
         result,
+        state_hash: game.state_hash_for(issuing_team),
+    };
+}
+
+fn handle_add_ai_player(
+    game_map: &GameMap,
+    game_id: &GameID,
+    team_id: TeamID,
+    difficulty: Difficulty,
+) -> ServerToClient {
+    let mut binding = game_map.lock().unwrap();
+    let game = match binding.get_mut(game_id) {
+        None => return ServerToClient::new_error(format!("No game found with id {}", game_id)),
+        Some(v) => v,
+    };
+
+    let team_taken = game.players.iter().any(|(_, team)| *team == team_id)
+        || game.ai_players.iter().any(|(team, _)| *team == team_id);
+    if team_taken || !game.scenario_state.teams.contains(&team_id) {
+        return ServerToClient::new_error(format!("Team {} is not available", team_id));
+    }
+
+    game.ai_players.push((team_id, difficulty));
+
+    return ServerToClient::AddAiPlayerResult {
+        game_id: *game_id,
+        team_id,
+    };
+}
+
+fn handle_request_resync(
+    game_map: &GameMap,
+    game_id: &GameID,
+    player_id: &PlayerID,
+) -> ServerToClient {
+    let binding = game_map.lock().unwrap();
+    let game = match binding.get(game_id) {
+        None => return ServerToClient::new_error(format!("No game found with id {}", game_id)),
+        Some(v) => v,
+    };
+
+    let Some((_, team_id)) = game.players.iter().find(|(id, _)| id == player_id) else {
+        return ServerToClient::new_error("Not in this game".to_string());
+    };
+
+    return ServerToClient::Resync {
+        game_id: *game_id,
+        scenario_state: game.scenario_state.view_for_team(*team_id),
+    };
+}
+
+fn handle_get_replay(game_map: &GameMap, game_id: &GameID) -> ServerToClient {
+    let binding = game_map.lock().unwrap();
+    let game = match binding.get(game_id) {
+        None => return ServerToClient::new_error(format!("No game found with id {}", game_id)),
+        Some(v) => v,
+    };
+
+    // The replay shows everything, fog included.
+    if !game.completed {
+        return ServerToClient::new_error("Game is still in progress".to_string());
+    }
+
+    return ServerToClient::ReplayResult {
+        game_id: *game_id,
+        replay: Box::new(game.replay.clone()),
     };
 }
 
@@ -231,7 +434,11 @@ fn handle_connect_to_game(
 
     if game.players.iter().any(|(existing_player, existing_team)| {
         existing_player == player_id || *existing_team == team_id
-    }) {
+    }) || game
+        .ai_players
+        .iter()
+        .any(|(ai_team, _)| *ai_team == team_id)
+    {
         let err_msg = format!("Player {} or team {} already occupied ", player_id, team_id);
         return ServerToClient::new_error(err_msg);
     }
@@ -240,7 +447,7 @@ fn handle_connect_to_game(
 
     return ServerToClient::ConnectToGameResult {
         game_id: *game_id,
-        scenario_state: game.scenario_state.clone(),
+        scenario_state: game.scenario_state.view_for_team(team_id),
         team_id: team_id,
     };
 }
@@ -292,3 +499,45 @@ fn send_response(
 
     recp.unbounded_send(Message::text(serde_json::to_string(message).unwrap()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ai_games_play_one_round_per_call() {
+        let mut game = Game::new(new_scenario_state(GameRules::default()));
+        game.ai_players = vec![(0, Difficulty::Easy), (1, Difficulty::Easy)];
+        let game_id = game.id;
+        let game_map: GameMap = Arc::new(Mutex::new(HashMap::from([(game_id, game)])));
+        let peer_map: PeerMap = Arc::new(Mutex::new(HashMap::new()));
+
+        play_ai_turns(&game_map, &game_id, &peer_map);
+
+        let games = game_map.lock().unwrap();
+        let state = &games[&game_id].scenario_state;
+        assert_eq!(state.turn, 2);
+        assert_eq!(state.active_team, 0);
+    }
+
+    #[test]
+    fn resyncs_match_the_hash_sent_to_the_team() {
+        let rules = GameRules {
+            fog: true,
+            ..Default::default()
+        };
+        let mut game = Game::new(new_scenario_state(rules));
+        let player_id: PlayerID = "127.0.0.1:1234".parse().unwrap();
+        game.players.push((player_id, 1));
+        let game_id = game.id;
+        let state_hash = game.state_hash_for(1);
+        let game_map: GameMap = Arc::new(Mutex::new(HashMap::from([(game_id, game)])));
+
+        let ServerToClient::Resync { scenario_state, .. } =
+            handle_request_resync(&game_map, &game_id, &player_id)
+        else {
+            panic!("Expected a resync");
+        };
+        assert_eq!(scenario_state.state_hash(), state_hash);
+    }
+}
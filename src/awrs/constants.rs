@@ -1 +1,2 @@
 pub const TILE_SIZE: f32 = 16.0;
+pub const QUICKSAVE_PATH: &str = "saves/quicksave.json";
@@ -1,7 +1,15 @@
+use std::{fs, path::Path};
+
+use advance_craft_engine::{replay::Replay, save::SaveFile};
 use bevy::ecs::event::*;
 use bevy::prelude::*;
 
 use crate::awrs::{
+    constants::QUICKSAVE_PATH,
+    plugins::{
+        interface::interface::{History, ScenarioState},
+        setup::build_map::RedrawEvent,
+    },
     register_inputs::InputEvent,
     resources::{
         action_event::{Action, ActionEvent, ActionResultEvent},
@@ -27,7 +35,13 @@ pub fn open_game_menu(
 
     // TODO get unit menu options from selected unit.
     // eg. Move if hasn't moved yet. Attack if unit next to it etc.
-    let options = vec!["E to End Turn", "Enter to Return to game"];
+    let options = vec![
+        "E to End Turn",
+        "U to Undo",
+        "F5 to Save",
+        "F9 to Load",
+        "Enter to Return to game",
+    ];
 
     commands
         .spawn((
@@ -68,6 +82,9 @@ pub fn game_menu_input(
     mut ev_action: EventWriter<ActionEvent>,
     mut next_game_state: ResMut<NextState<GameState>>,
     mut next_menu_state: ResMut<NextState<MenuState>>,
+    mut ev_redraw: EventWriter<RedrawEvent>,
+    mut scenario_state: ResMut<ScenarioState>,
+    mut history: ResMut<History>,
 ) {
     let mut cursor = input_events.get_cursor();
     let mut should_clear = false;
@@ -75,9 +92,39 @@ pub fn game_menu_input(
         match ev {
             InputEvent::EndTurn => {
                 info!("Reading input events... Ending Turn");
+                let pending = scenario_state.units_pending_action(scenario_state.active_team);
+                if !pending.is_empty() {
+                    warn!("Ending turn with {} units yet to act", pending.len());
+                }
                 ev_action.send(ActionEvent(Action::EndTurn));
                 should_clear = true;
             }
+            InputEvent::Undo => {
+                if scenario_state.undo() {
+                    info!("Undid last command");
+                    history.undo();
+                    ev_redraw.send(RedrawEvent);
+                    next_game_state.set(GameState::Browsing);
+                    next_menu_state.set(MenuState::Closed);
+                } else {
+                    warn!("Nothing to undo");
+                }
+                should_clear = true;
+            }
+            InputEvent::Save => {
+                save_game(&scenario_state, &history);
+                should_clear = true;
+            }
+            InputEvent::Load => {
+                if let Some(save) = load_game() {
+                    scenario_state.0 = save.scenario_state;
+                    history.0 = save.replay.unwrap_or_else(|| Replay::new(&scenario_state));
+                    ev_redraw.send(RedrawEvent);
+                    next_game_state.set(GameState::Browsing);
+                    next_menu_state.set(MenuState::Closed);
+                }
+                should_clear = true;
+            }
             InputEvent::ToggleMenu => {
                 info!("Quitting menu");
 
@@ -93,19 +140,61 @@ pub fn game_menu_input(
     }
 }
 
+fn save_game(scenario_state: &ScenarioState, history: &History) {
+    let save = SaveFile::new("Quicksave", scenario_state, Some(history.0.clone()));
+    let json = match save.to_json() {
+        Ok(json) => json,
+        Err(err) => {
+            error!("Couldn't save game: {:?}", err);
+            return;
+        }
+    };
+
+    if let Some(dir) = Path::new(QUICKSAVE_PATH).parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    match fs::write(QUICKSAVE_PATH, json) {
+        Ok(_) => info!("Saved game to {}", QUICKSAVE_PATH),
+        Err(err) => error!("Couldn't save game: {:?}", err),
+    }
+}
+
+fn load_game() -> Option<SaveFile> {
+    let json = fs::read_to_string(QUICKSAVE_PATH)
+        .map_err(|err| warn!("Couldn't read {}: {:?}", QUICKSAVE_PATH, err))
+        .ok()?;
+    let save = SaveFile::from_json(&json)
+        .map_err(|err| error!("Couldn't load save: {:?}", err))
+        .ok()?;
+    info!(
+        "Loaded \"{}\" from turn {}",
+        save.metadata.name, save.metadata.turn
+    );
+    Some(save)
+}
+
 pub fn end_turn_result(
+    mut commands: Commands,
     mut ev_action_result: EventReader<ActionResultEvent>,
-    mut q_units: Query<&mut Sprite, With<UnitId>>,
+    mut q_units: Query<(Entity, &UnitId, &mut Sprite)>,
+    scenario_state: Res<ScenarioState>,
     mut active_team: ResMut<ActiveTeam>,
     mut next_game_state: ResMut<NextState<GameState>>,
     mut next_menu_state: ResMut<NextState<MenuState>>,
 ) {
     for action_result in ev_action_result.read() {
-        if let ActionResultEvent::EndTurnResult(new_active_team) = action_result {
+        if let ActionResultEvent::EndTurnResult(new_active_team, _) = action_result {
             info!("Handling end turn action result");
             active_team.team = *new_active_team;
 
-            for mut sprite in q_units.iter_mut() {
+            for (entity, UnitId(unit_id), mut sprite) in q_units.iter_mut() {
+                // Units which ran out of fuel have crashed.
+                if scenario_state.get_unit(*unit_id).is_none()
+                    && !scenario_state.is_carried(*unit_id)
+                {
+                    commands.entity(entity).despawn_recursive();
+                    continue;
+                }
                 sprite.color = Color::WHITE;
             }
 
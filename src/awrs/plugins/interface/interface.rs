@@ -1,17 +1,30 @@
+use advance_craft_ai::{play_turn, Agent};
 use advance_craft_engine::{
-    Command, CommandResult, ScenarioState as EngineScenarioState, Tile as EngineTile,
+    replay::Replay, rules::GameOutcome, Command, CommandResult,
+    ScenarioState as EngineScenarioState, TeamID, Tile as EngineTile,
 };
 use bevy::prelude::*;
 
-use crate::awrs::resources::{
-    action_event::{Action, ActionEvent, ActionResultEvent, Attack},
-    tile::Tile,
-    unit::UnitId,
+use crate::awrs::{
+    plugins::setup::build_map::RedrawEvent,
+    resources::{
+        action_event::{Action, ActionEvent, ActionResultEvent, Attack},
+        tile::Tile,
+        unit::UnitId,
+    },
 };
 
 #[derive(Deref, DerefMut, Resource)]
 pub struct ScenarioState(pub EngineScenarioState);
 
+// Every command played so far, kept for save files.
+#[derive(Deref, DerefMut, Resource)]
+pub struct History(pub Replay);
+
+// The teams played by the computer, each with the agent choosing its commands.
+#[derive(Resource, Default)]
+pub struct AiPlayers(pub Vec<(TeamID, Box<dyn Agent>)>);
+
 impl From<&Tile> for EngineTile {
     fn from(tile: &Tile) -> EngineTile {
         EngineTile {
@@ -24,28 +37,59 @@ impl From<&Tile> for EngineTile {
 impl From<CommandResult> for ActionResultEvent {
     fn from(command_result: CommandResult) -> ActionResultEvent {
         match command_result {
-            CommandResult::Move { status: _, tiles } => ActionResultEvent::MoveResult(
+            CommandResult::Move {
+                tiles, trapped_by, ..
+            } => ActionResultEvent::MoveResult(
                 tiles
                     .iter()
                     .map(|EngineTile { x, y }| Tile { x: *x, y: *y })
                     .collect(),
+                trapped_by.map(|unit| UnitId(unit.id)),
             ),
             CommandResult::Attack {
-                status: _,
                 unit_hp_changes: unit_hp,
+                rank_changes,
+                ..
+            }
+            | CommandResult::AttackGround {
+                unit_hp_changes: unit_hp,
+                rank_changes,
+                ..
             } => ActionResultEvent::AttackResult(
                 unit_hp.iter().map(|(id, hp)| (UnitId(*id), *hp)).collect(),
+                rank_changes
+                    .iter()
+                    .map(|(id, rank)| (UnitId(*id), *rank))
+                    .collect(),
             ),
-            CommandResult::AttackGround {
-                status: _,
+            CommandResult::Repair {
+                unit_hp_changes: unit_hp,
+                ..
+            }
+            | CommandResult::Heal {
                 unit_hp_changes: unit_hp,
+                ..
             } => ActionResultEvent::AttackResult(
                 unit_hp.iter().map(|(id, hp)| (UnitId(*id), *hp)).collect(),
+                vec![],
             ),
+            // No hp changes, but the unit is greyed out like any other that has finished.
+            CommandResult::Wait { .. } => ActionResultEvent::AttackResult(vec![], vec![]),
             CommandResult::EndTurn {
-                status: _,
                 new_active_team,
-            } => ActionResultEvent::EndTurnResult(new_active_team),
+                unit_hp_changes: unit_hp,
+                ..
+            } => ActionResultEvent::EndTurnResult(
+                new_active_team,
+                unit_hp.iter().map(|(id, hp)| (UnitId(*id), *hp)).collect(),
+            ),
+            CommandResult::Research { .. }
+            | CommandResult::Build { .. }
+            | CommandResult::Construct { .. }
+            | CommandResult::Morph { .. }
+            | CommandResult::Load { .. }
+            | CommandResult::Unload { .. }
+            | CommandResult::Act { .. } => ActionResultEvent::Unhandled,
         }
     }
 }
@@ -54,6 +98,7 @@ pub fn handle_action(
     mut ev_action: EventReader<ActionEvent>,
     mut ev_action_result: EventWriter<ActionResultEvent>,
     mut scenario_state: ResMut<ScenarioState>,
+    mut history: ResMut<History>,
     q_units: Query<&UnitId>,
 ) {
     for ActionEvent(action) in ev_action.read() {
@@ -92,12 +137,45 @@ pub fn handle_action(
                     tiles: tiles.iter().map(|tile| EngineTile::from(tile)).collect(),
                 }
             }
+            Action::Wait { entity } => {
+                let unit = q_units.get(*entity).expect("Unable to find unit");
+                Command::Wait { unit_id: unit.0 }
+            }
             Action::EndTurn => Command::EndTurn,
         };
 
         info!("Sending Action Result Event! ({:?})", command);
-        let result = scenario_state.execute(command);
+        let result = scenario_state.execute(command.clone());
         info!("{:?}", result);
+        history.record_played(command, result.clone(), &scenario_state);
         ev_action_result.send(ActionResultEvent::from(result));
     }
 }
+
+/// Plays the whole turn when it's a computer team's go, then redraws the map to show what
+/// happened.
+pub fn play_ai_turn(
+    mut scenario_state: ResMut<ScenarioState>,
+    mut history: ResMut<History>,
+    mut ai_players: ResMut<AiPlayers>,
+    mut ev_redraw: EventWriter<RedrawEvent>,
+) {
+    if scenario_state.outcome() != GameOutcome::Ongoing {
+        return;
+    }
+    let team = scenario_state.active_team;
+    let Some((_, agent)) = ai_players
+        .0
+        .iter_mut()
+        .find(|(ai_team, _)| *ai_team == team)
+    else {
+        return;
+    };
+
+    info!("Playing the turn for AI team {}", team);
+    for (command, result) in play_turn(agent.as_mut(), &mut scenario_state) {
+        info!("AI played {:?}: {:?}", command, result.status());
+        history.record_played(command, result, &scenario_state);
+    }
+    ev_redraw.send(RedrawEvent);
+}
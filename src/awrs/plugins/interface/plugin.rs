@@ -3,11 +3,16 @@ use bevy::prelude::*;
 use super::interface::*;
 
 use crate::awrs::{
-    plugins::movement_plan::move_result,
+    plugins::{
+        game_menu::game_menu::end_turn_result,
+        movement_plan::{animate_trap_alert, move_result},
+    },
     resources::{
         action_event::{ActionEvent, ActionResultEvent},
-        state::AppState,
-        unit::{handle_attack_result, handle_damage},
+        state::{AppState, GameState},
+        unit::{
+            handle_attack_result, handle_cargo, handle_damage, handle_morphing, handle_rank_changes,
+        },
     },
 };
 
@@ -22,11 +27,31 @@ impl Plugin for InterfacePlugin {
             .add_event::<ActionResultEvent>()
             .configure_sets(Update, InputSet.run_if(in_state(AppState::InGame)))
             .add_systems(Update, (handle_action.in_set(InputSet),))
+            // Only once the player has finished whatever they were doing.
+            .add_systems(
+                Update,
+                play_ai_turn
+                    .in_set(InputSet)
+                    .run_if(in_state(GameState::Browsing)),
+            )
             .add_systems(
                 Update,
-                (handle_attack_result, handle_damage, move_result)
+                (
+                    handle_attack_result,
+                    handle_damage,
+                    handle_rank_changes,
+                    move_result,
+                    animate_trap_alert,
+                )
                     .in_set(InputSet)
                     .after(handle_action),
+            )
+            .add_systems(
+                Update,
+                (handle_morphing, handle_cargo)
+                    .in_set(InputSet)
+                    .after(handle_attack_result)
+                    .after(end_turn_result),
             );
         // ! There has to be a better way! Split AppState & GameState?
         // .add_system_set(
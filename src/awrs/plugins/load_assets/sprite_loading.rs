@@ -116,6 +116,18 @@ pub fn create_idle_sprites(
         (UnitType::Baneling, "spritesheets/baneling_idle.png"),
         (UnitType::Roach, "spritesheets/roach_idle.png"),
         (UnitType::SiegeTank, "spritesheets/tank_idle.png"),
+        // Placeholders until these units have their own sprites
+        (UnitType::Scv, "spritesheets/marine_idle.png"),
+        (UnitType::Drone, "spritesheets/zergling_idle.png"),
+        (UnitType::Ravager, "spritesheets/roach_idle.png"),
+        (UnitType::Probe, "spritesheets/marine_idle.png"),
+        (UnitType::Zealot, "spritesheets/zergling_idle.png"),
+        (UnitType::Stalker, "spritesheets/roach_idle.png"),
+        (UnitType::Immortal, "spritesheets/tank_idle.png"),
+        (UnitType::Mutalisk, "spritesheets/zergling_idle.png"),
+        (UnitType::Viking, "spritesheets/tank_idle.png"),
+        (UnitType::Medivac, "spritesheets/marine_idle.png"),
+        (UnitType::Overlord, "spritesheets/baneling_idle.png"),
     ];
 
     for (unit_type, idle_path) in units {
@@ -150,6 +162,35 @@ pub fn create_structure_sprites(
             "spritesheets/command_centre.png",
         ),
         (StructureType::Hatchery, "spritesheets/hatchery.png"),
+        // Placeholders until these structures have their own sprites
+        (StructureType::Barracks, "spritesheets/command_centre.png"),
+        (StructureType::Factory, "spritesheets/command_centre.png"),
+        (
+            StructureType::EngineeringBay,
+            "spritesheets/command_centre.png",
+        ),
+        (StructureType::SpawningPool, "spritesheets/hatchery.png"),
+        (StructureType::RoachWarren, "spritesheets/hatchery.png"),
+        (StructureType::EvolutionChamber, "spritesheets/hatchery.png"),
+        (StructureType::BanelingNest, "spritesheets/hatchery.png"),
+        (StructureType::Nexus, "spritesheets/command_centre.png"),
+        (StructureType::Pylon, "spritesheets/command_centre.png"),
+        (StructureType::Gateway, "spritesheets/command_centre.png"),
+        (
+            StructureType::CyberneticsCore,
+            "spritesheets/command_centre.png",
+        ),
+        (
+            StructureType::RoboticsFacility,
+            "spritesheets/command_centre.png",
+        ),
+        (StructureType::Spire, "spritesheets/hatchery.png"),
+        (StructureType::NydusNetwork, "spritesheets/hatchery.png"),
+        (
+            StructureType::SupplyDepot,
+            "spritesheets/command_centre.png",
+        ),
+        (StructureType::Starport, "spritesheets/command_centre.png"),
     ];
 
     for (structure_type, spritesheet_path) in structures {
@@ -270,16 +270,21 @@ pub fn confirm_move(
     }
 }
 
+#[derive(Component)]
+pub struct TrapAlert(Timer);
+
 pub fn move_result(
+    mut commands: Commands,
     mut ev_move_result: EventReader<ActionResultEvent>,
     mut next_state: ResMut<NextState<GameState>>,
+    q_selected: Query<Entity, With<Selected>>,
     mut q: ParamSet<(
         Query<&mut Transform, With<Selected>>,
         Query<&mut Transform, With<Cursor>>,
     )>,
 ) {
     for action_result in ev_move_result.read() {
-        if let ActionResultEvent::MoveResult(tiles) = action_result {
+        if let ActionResultEvent::MoveResult(tiles, trapped_by) = action_result {
             info!("Executing move_result");
             if let Some(location) = tiles.last() {
                 info!("Moving unit...");
@@ -296,11 +301,42 @@ pub fn move_result(
             } else {
             }
 
+            if trapped_by.is_some() {
+                info!("Ambushed!");
+                let entity = q_selected.single();
+                commands.entity(entity).with_children(|parent| {
+                    parent.spawn((
+                        TrapAlert(Timer::from_seconds(1.0, TimerMode::Once)),
+                        Text2d::new("!"),
+                        TextFont {
+                            font_size: 10.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(1.0, 0.0, 0.0)),
+                        Transform::from_translation(Vec3::new(0.0, 12.0, 5.0)),
+                    ));
+                });
+            }
+
             next_state.set(GameState::Browsing);
         }
     }
 }
 
+pub fn animate_trap_alert(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut q_alert: Query<(Entity, &mut TrapAlert, &mut Transform)>,
+) {
+    for (entity, mut alert, mut transform) in q_alert.iter_mut() {
+        alert.0.tick(time.delta());
+        transform.translation.y = 12.0 + (alert.0.elapsed_secs() * 20.0).sin() * 2.0;
+        if alert.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
 pub fn exit_movement_plan(mut unit_plan: ResMut<UnitPlan>, mut commands: Commands) {
     for step in unit_plan.steps.iter() {
         commands.entity(step.entity).despawn();
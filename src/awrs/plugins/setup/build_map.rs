@@ -1,9 +1,16 @@
-use advance_craft_engine::{dev_helpers::new_scenario_state, TerrainType};
+use advance_craft_ai::{Agent, Difficulty, GreedyAgent};
+use advance_craft_engine::{
+    dev_helpers::new_scenario_state,
+    replay::Replay,
+    rules::{GameRules, UndoPolicy},
+    units::air::Layer,
+    TerrainType,
+};
 use bevy::prelude::*;
 
 use crate::awrs::{
     constants::*,
-    plugins::interface::interface::ScenarioState,
+    plugins::interface::interface::{AiPlayers, History, ScenarioState},
     resources::{
         animation::AnimationConfig,
         atlases::{CreepAtlas, HealthAtlas, StructureAtlases, TerrainAtlas, UnitAtlases},
@@ -24,7 +31,11 @@ pub fn build_map(
     creep_atlas: Res<CreepAtlas>,
 ) {
     info!("Building map");
-    let scenario_state = ScenarioState(new_scenario_state());
+    let rules = GameRules {
+        undo: UndoPolicy::BeforeReveal,
+        ..Default::default()
+    };
+    let scenario_state = ScenarioState(new_scenario_state(rules));
 
     let mut projection = OrthographicProjection::default_2d();
     projection.scale /= SCALE;
@@ -40,13 +51,83 @@ pub fn build_map(
     spawn_tiles(&mut commands, &scenario_state, &terrain_atlas);
     spawn_creep(&mut commands, &scenario_state, &creep_atlas);
 
+    spawn_pieces(
+        &mut commands,
+        &scenario_state,
+        &unit_atlases,
+        &structure_atlases,
+        &health_atlas,
+    );
+    commands.insert_resource(History(Replay::new(&scenario_state)));
+    // The zerg are played by the computer.
+    let agent: Box<dyn Agent> = Box::new(GreedyAgent::new(
+        Difficulty::Normal,
+        scenario_state.rules.seed,
+    ));
+    commands.insert_resource(AiPlayers(vec![(1, agent)]));
+    commands.insert_resource(scenario_state);
+}
+
+/// Sent when the engine state has jumped, eg. after an undo or loading a save, so the whole
+/// map has to be redrawn.
+#[derive(Event)]
+pub struct RedrawEvent;
+
+pub fn redraw_map(
+    mut commands: Commands,
+    mut ev_redraw: EventReader<RedrawEvent>,
+    q_drawn: Query<Entity, Or<(With<GameMap>, With<UnitId>, With<StructureId>, With<Creep>)>>,
+    scenario_state: Res<ScenarioState>,
+    mut active_team: ResMut<ActiveTeam>,
+    terrain_atlas: Res<TerrainAtlas>,
+    unit_atlases: Res<UnitAtlases>,
+    structure_atlases: Res<StructureAtlases>,
+    health_atlas: Res<HealthAtlas>,
+    creep_atlas: Res<CreepAtlas>,
+) {
+    if ev_redraw.read().count() == 0 {
+        return;
+    }
+
+    info!("Redrawing map");
+    for entity in q_drawn.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    active_team.team = scenario_state.active_team;
+    spawn_tiles(&mut commands, &scenario_state, &terrain_atlas);
+    spawn_creep(&mut commands, &scenario_state, &creep_atlas);
+    spawn_pieces(
+        &mut commands,
+        &scenario_state,
+        &unit_atlases,
+        &structure_atlases,
+        &health_atlas,
+    );
+}
+
+fn spawn_pieces(
+    commands: &mut Commands,
+    scenario_state: &advance_craft_engine::ScenarioState,
+    unit_atlases: &Res<UnitAtlases>,
+    structure_atlases: &Res<StructureAtlases>,
+    health_atlas: &Res<HealthAtlas>,
+) {
     for unit in scenario_state.units.iter() {
-        spawn_unit(&mut commands, unit, &unit_atlases, &health_atlas);
+        spawn_unit(commands, scenario_state, unit, unit_atlases, health_atlas);
     }
     for structure in scenario_state.structures.iter() {
-        spawn_structure(&mut commands, structure, &structure_atlases, &health_atlas);
+        spawn_structure(commands, structure, structure_atlases, health_atlas);
+    }
+}
+
+// Matches the indicator shown by handle_damage.
+fn health_indicator(health_percent: f32) -> (usize, Visibility) {
+    let ceil_health = (health_percent * 10.0).ceil().max(1.0) as usize;
+    if ceil_health < 10 {
+        (ceil_health - 1, Visibility::Visible)
+    } else {
+        (9, Visibility::Hidden)
     }
-    commands.insert_resource(scenario_state);
 }
 
 #[derive(Component)]
@@ -113,8 +194,15 @@ fn spawn_tiles(
                                 index: match terrain_type {
                                     TerrainType::Water => 0,
                                     TerrainType::Grass => 1,
+                                    // No sprites yet, so these are tinted grass.
+                                    TerrainType::Mountain | TerrainType::HighGround => 1,
                                 },
                             }),
+                            color: match terrain_type {
+                                TerrainType::Mountain => Color::srgb(0.45, 0.35, 0.25),
+                                TerrainType::HighGround => Color::srgb(0.8, 0.8, 0.6),
+                                _ => Color::WHITE,
+                            },
                             ..Default::default()
                         },
                         Transform::from_translation(Vec3::new(
@@ -130,6 +218,7 @@ fn spawn_tiles(
 
 fn spawn_unit(
     commands: &mut Commands,
+    scenario_state: &advance_craft_engine::ScenarioState,
     unit: &advance_craft_engine::Unit,
     unit_atlases: &Res<UnitAtlases>,
     health_atlas: &Res<HealthAtlas>,
@@ -142,6 +231,20 @@ fn spawn_unit(
         .unwrap();
 
     let animation_config = AnimationConfig::new(0, 3, 2);
+    // Air units are drawn above any ground unit sharing their tile.
+    let z = match unit.layer() {
+        Layer::Ground => 1.0,
+        Layer::Air => 2.0,
+    };
+    let rank = unit.rank;
+    let (health_index, health_visibility) =
+        health_indicator(unit.health / scenario_state.get_max_health(unit));
+    const GRAY: Srgba = bevy::color::palettes::css::GRAY;
+    let color = if scenario_state.unit_cannot_act(&unit.id) {
+        GRAY.into()
+    } else {
+        Color::WHITE
+    };
 
     commands
         .spawn((
@@ -153,9 +256,10 @@ fn spawn_unit(
                     index: 0,
                 }),
                 flip_x: unit.team % 2 == 0,
+                color,
                 ..Default::default()
             },
-            Transform::from_translation(Vec3::new(x as f32 * TILE_SIZE, y as f32 * TILE_SIZE, 1.0)),
+            Transform::from_translation(Vec3::new(x as f32 * TILE_SIZE, y as f32 * TILE_SIZE, z)),
             animation_config,
         ))
         .with_children(|unit| {
@@ -164,13 +268,23 @@ fn spawn_unit(
                 Sprite {
                     texture_atlas: Some(TextureAtlas {
                         layout: health_atlas.layout.clone(),
-                        index: 9,
+                        index: health_index,
                     }),
                     image: health_atlas.texture.clone(),
                     ..Default::default()
                 },
                 Transform::from_translation(Vec3::new(7.0, 7.0, 4.0)),
-                Visibility::Hidden,
+                health_visibility,
+            ));
+            unit.spawn((
+                RankIndicator,
+                Text2d::new(rank_chevrons(rank)),
+                TextFont {
+                    font_size: 8.0,
+                    ..Default::default()
+                },
+                TextColor(Color::from(bevy::color::palettes::css::GOLD)),
+                Transform::from_translation(Vec3::new(-6.0, 7.0, 4.0)),
             ));
         });
 }
@@ -181,8 +295,10 @@ fn spawn_structure(
     structure_atlases: &Res<StructureAtlases>,
     health_atlas: &Res<HealthAtlas>,
 ) {
-    let x = structure.position.x;
-    let y = structure.position.y;
+    // Stretch the sprite over every tile the structure covers.
+    let (width, height) = structure.structure_type.value().footprint;
+    let x = structure.position.x as f32 + (width - 1) as f32 / 2.0;
+    let y = structure.position.y as f32 + (height - 1) as f32 / 2.0;
     let texture_atlas = structure_atlases
         .atlas_map
         .get(&StructureType::from(structure.structure_type))
@@ -197,9 +313,13 @@ fn spawn_structure(
                     layout: texture_atlas.layout.clone(),
                     index: 0,
                 }),
+                custom_size: Some(Vec2::new(
+                    width as f32 * TILE_SIZE,
+                    height as f32 * TILE_SIZE,
+                )),
                 ..Default::default()
             },
-            Transform::from_translation(Vec3::new(x as f32 * TILE_SIZE, y as f32 * TILE_SIZE, 0.9)),
+            Transform::from_translation(Vec3::new(x * TILE_SIZE, y * TILE_SIZE, 0.9)),
             AnimationConfig::new(0, 3, 2),
         ))
         .with_children(|unit| {
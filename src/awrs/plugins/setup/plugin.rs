@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use super::build_map::build_map;
+use super::build_map::{build_map, redraw_map, RedrawEvent};
 use super::cursor::{create_cursor, handle_change_cursor};
 
 use crate::awrs::resources::cursor::SelectEvent;
@@ -17,7 +17,8 @@ impl Plugin for SetupPlugin {
             .add_event::<ChangeCursorEvent>()
             .add_event::<SelectEvent>()
             .add_event::<InputEvent>()
-            .add_systems(Update, handle_change_cursor)
+            .add_event::<RedrawEvent>()
+            .add_systems(Update, (handle_change_cursor, redraw_map))
             .add_systems(
                 OnEnter(GameState::SetUp),
                 (build_map, create_cursor, transition_to_browsing).chain(),
@@ -29,6 +29,43 @@ pub fn open_target_selection(
     }
 }
 
+#[derive(Component)]
+pub struct TargetMarker;
+
+// Marks the units the selected unit can actually hit, taking range and line of sight into account.
+pub fn show_hittable_targets(
+    mut commands: Commands,
+    attacking_unit_query: Query<&UnitId, With<Selected>>,
+    units_query: Query<(Entity, &UnitId), Without<Selected>>,
+    scenario_state: Res<ScenarioState>,
+) {
+    let attacker_id = attacking_unit_query.single();
+    let targets = scenario_state.get_targets_in_range(attacker_id.0);
+    for (entity, unit_id) in units_query.iter() {
+        if !targets.contains(&unit_id.0) {
+            continue;
+        }
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                TargetMarker,
+                Text2d::new("+"),
+                TextFont {
+                    font_size: 12.0,
+                    ..Default::default()
+                },
+                TextColor(Color::from(bevy::color::palettes::css::RED)),
+                Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+            ));
+        });
+    }
+}
+
+pub fn hide_hittable_targets(mut commands: Commands, q_markers: Query<Entity, With<TargetMarker>>) {
+    for entity in q_markers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
 pub fn target_select(
     mut next_state: ResMut<NextState<GameState>>,
     mut attacking_unit_query: Query<(Entity, &UnitId), With<Selected>>,
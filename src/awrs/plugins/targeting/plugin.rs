@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 
-use super::choose_target::{open_target_selection, target_select};
+use super::choose_target::{
+    hide_hittable_targets, open_target_selection, show_hittable_targets, target_select,
+};
 
 use crate::awrs::resources::{
     cursor::{handle_cursor_move, handle_cursor_select},
@@ -18,7 +20,11 @@ impl Plugin for TargetingPlugin {
             Update,
             ChooseTargetSet.run_if(in_state(GameState::ChooseTarget)),
         )
-        .add_systems(OnEnter(GameState::ChooseTarget), open_target_selection)
+        .add_systems(
+            OnEnter(GameState::ChooseTarget),
+            (open_target_selection, show_hittable_targets),
+        )
+        .add_systems(OnExit(GameState::ChooseTarget), hide_hittable_targets)
         .add_systems(
             Update,
             (handle_cursor_move, handle_cursor_select, target_select).in_set(ChooseTargetSet),
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 
 use crate::awrs::resources::{
+    action_event::{Action, ActionEvent},
     cursor::{ChangeCursorEvent, CursorStyle},
     state::GameState,
     unit::{Selected, UnitId},
@@ -23,7 +24,7 @@ pub fn open_unit_menu(
 
         // TODO get unit menu options from selected unit.
         // eg. Move if hasn't moved yet. Attack if unit next to it etc.
-        let options = vec!["M - Move", "T - Attack", "C - Cancel"];
+        let options = vec!["M - Move", "T - Attack", "H - Wait", "C - Cancel"];
 
         commands
             .spawn((
@@ -65,6 +66,7 @@ pub fn unit_menu_input(
     mut next_state: ResMut<NextState<GameState>>,
     units_query: Query<Entity, (With<Selected>, With<UnitId>)>,
     mut commands: Commands,
+    mut ev_action: EventWriter<ActionEvent>,
 ) {
     if keyboard_input.just_pressed(KeyCode::KeyM) {
         info!("Changing Game State to MoveUnit");
@@ -75,6 +77,17 @@ pub fn unit_menu_input(
 
         next_state.set(GameState::ChooseTarget);
     }
+    if keyboard_input.just_pressed(KeyCode::KeyH) {
+        info!("Unit waiting. Returning to Browse");
+        if let Ok(unit_entity) = units_query.get_single() {
+            ev_action.send(ActionEvent(Action::Wait {
+                entity: unit_entity,
+            }));
+            commands.entity(unit_entity).remove::<Selected>();
+        }
+
+        next_state.set(GameState::Browsing);
+    }
     if keyboard_input.just_pressed(KeyCode::KeyC) {
         info!("Cancel selected. Returning to Browse");
         if let Ok(unit_entity) = units_query.get_single() {
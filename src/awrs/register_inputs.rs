@@ -16,6 +16,9 @@ pub enum InputEvent {
     Attack,
     Cancel,
     EndTurn,
+    Undo,
+    Save,
+    Load,
 }
 
 pub fn register_inputs(
@@ -73,4 +76,19 @@ pub fn register_inputs(
         ev_input.send(InputEvent::EndTurn);
         return;
     }
+
+    if keyboard_input.just_pressed(KeyCode::KeyU) {
+        ev_input.send(InputEvent::Undo);
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        ev_input.send(InputEvent::Save);
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::F9) {
+        ev_input.send(InputEvent::Load);
+        return;
+    }
 }
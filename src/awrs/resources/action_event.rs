@@ -2,7 +2,7 @@ use bevy::prelude::*;
 
 use super::{tile::Tile, unit::UnitId};
 
-use advance_craft_engine::UnitHp;
+use advance_craft_engine::{units::veterancy::Rank, UnitHp};
 
 // Do we _need_ a wrapper here? Can the event be the enum?
 #[derive(Event)]
@@ -12,6 +12,7 @@ pub struct ActionEvent(pub Action);
 pub enum Action {
     Attack(Attack),
     Move { entity: Entity, tiles: Vec<Tile> },
+    Wait { entity: Entity },
     EndTurn,
 }
 
@@ -36,7 +37,8 @@ pub enum Attack {
 // Will need to add more detail once its clear what is needed from these result events.
 #[derive(Event)]
 pub enum ActionResultEvent {
-    AttackResult(Vec<(UnitId, UnitHp)>), // Include ammo in this struct?
-    MoveResult(Vec<Tile>),
-    EndTurnResult(u32),
+    AttackResult(Vec<(UnitId, UnitHp)>, Vec<(UnitId, Rank)>), // Include ammo in this struct?
+    MoveResult(Vec<Tile>, Option<UnitId>), // Includes the hidden unit that stopped the move
+    EndTurnResult(u32, Vec<(UnitId, UnitHp)>), // Includes healing at the start of the turn
+    Unhandled,                             // Results of commands the client has no UI for yet.
 }
@@ -2,10 +2,11 @@ use bevy::prelude::*;
 
 use advance_craft_engine::structures::structures::StructureType as EngineStructureType;
 use advance_craft_engine::units::units::UnitType as EngineUnitType;
+use advance_craft_engine::units::veterancy::Rank;
 
 use crate::awrs::plugins::interface::interface::ScenarioState;
 
-use super::action_event::ActionResultEvent;
+use super::{action_event::ActionResultEvent, atlases::UnitAtlases, tile::TILE_SIZE};
 
 type UnitHealth = f32;
 
@@ -24,6 +25,17 @@ pub enum UnitType {
     Baneling,
     Roach,
     SiegeTank,
+    Scv,
+    Drone,
+    Ravager,
+    Probe,
+    Zealot,
+    Stalker,
+    Immortal,
+    Mutalisk,
+    Viking,
+    Medivac,
+    Overlord,
 }
 
 impl From<EngineUnitType> for UnitType {
@@ -34,6 +46,17 @@ impl From<EngineUnitType> for UnitType {
             EngineUnitType::Baneling => UnitType::Baneling,
             EngineUnitType::Roach => UnitType::Roach,
             EngineUnitType::SiegeTank => UnitType::SiegeTank,
+            EngineUnitType::Scv => UnitType::Scv,
+            EngineUnitType::Drone => UnitType::Drone,
+            EngineUnitType::Ravager => UnitType::Ravager,
+            EngineUnitType::Probe => UnitType::Probe,
+            EngineUnitType::Zealot => UnitType::Zealot,
+            EngineUnitType::Stalker => UnitType::Stalker,
+            EngineUnitType::Immortal => UnitType::Immortal,
+            EngineUnitType::Mutalisk => UnitType::Mutalisk,
+            EngineUnitType::Viking => UnitType::Viking,
+            EngineUnitType::Medivac => UnitType::Medivac,
+            EngineUnitType::Overlord => UnitType::Overlord,
         }
     }
 }
@@ -45,6 +68,22 @@ pub struct StructureId(pub u32);
 pub enum StructureType {
     CommandCentre,
     Hatchery,
+    Barracks,
+    Factory,
+    EngineeringBay,
+    SpawningPool,
+    RoachWarren,
+    EvolutionChamber,
+    BanelingNest,
+    Nexus,
+    Pylon,
+    Gateway,
+    CyberneticsCore,
+    RoboticsFacility,
+    Spire,
+    Starport,
+    NydusNetwork,
+    SupplyDepot,
 }
 
 impl From<EngineStructureType> for StructureType {
@@ -52,6 +91,22 @@ impl From<EngineStructureType> for StructureType {
         match structure_type {
             EngineStructureType::CommandCentre => StructureType::CommandCentre,
             EngineStructureType::Hatchery => StructureType::Hatchery,
+            EngineStructureType::Barracks => StructureType::Barracks,
+            EngineStructureType::Factory => StructureType::Factory,
+            EngineStructureType::EngineeringBay => StructureType::EngineeringBay,
+            EngineStructureType::SpawningPool => StructureType::SpawningPool,
+            EngineStructureType::RoachWarren => StructureType::RoachWarren,
+            EngineStructureType::EvolutionChamber => StructureType::EvolutionChamber,
+            EngineStructureType::BanelingNest => StructureType::BanelingNest,
+            EngineStructureType::Nexus => StructureType::Nexus,
+            EngineStructureType::Pylon => StructureType::Pylon,
+            EngineStructureType::Gateway => StructureType::Gateway,
+            EngineStructureType::CyberneticsCore => StructureType::CyberneticsCore,
+            EngineStructureType::RoboticsFacility => StructureType::RoboticsFacility,
+            EngineStructureType::Spire => StructureType::Spire,
+            EngineStructureType::Starport => StructureType::Starport,
+            EngineStructureType::NydusNetwork => StructureType::NydusNetwork,
+            EngineStructureType::SupplyDepot => StructureType::SupplyDepot,
         }
     }
 }
@@ -59,6 +114,41 @@ impl From<EngineStructureType> for StructureType {
 #[derive(Component)]
 pub struct HPIndicator;
 
+#[derive(Component)]
+pub struct RankIndicator;
+
+pub fn rank_chevrons(rank: Rank) -> String {
+    match rank {
+        Rank::Rookie => String::new(),
+        _ => rank.value().name.to_string(),
+    }
+}
+
+pub fn handle_rank_changes(
+    mut ev_action_result: EventReader<ActionResultEvent>,
+    q_units: Query<(&UnitId, &Children)>,
+    mut q_rank_indicator: Query<&mut Text2d, With<RankIndicator>>,
+) {
+    for action_result in ev_action_result.read() {
+        let ActionResultEvent::AttackResult(_, rank_changes) = action_result else {
+            continue;
+        };
+        for (id, rank) in rank_changes {
+            for (unit_id, children) in q_units.iter() {
+                if unit_id.0 != id.0 {
+                    continue;
+                }
+                info!("Unit {} promoted to {:?}", id.0, rank);
+                for &child in children.iter() {
+                    if let Ok(mut text) = q_rank_indicator.get_mut(child) {
+                        **text = rank_chevrons(*rank);
+                    }
+                }
+            }
+        }
+    }
+}
+
 pub fn handle_attack_result(
     mut q_units: Query<(Entity, &UnitId, &mut Sprite)>,
     mut ev_action_result: EventReader<ActionResultEvent>,
@@ -66,7 +156,9 @@ pub fn handle_attack_result(
     scenario_state: Res<ScenarioState>,
 ) {
     for action_result in ev_action_result.read() {
-        if let ActionResultEvent::AttackResult(damaged_units) = action_result {
+        if let ActionResultEvent::AttackResult(damaged_units, _)
+        | ActionResultEvent::EndTurnResult(_, damaged_units) = action_result
+        {
             for (id, hp) in damaged_units {
                 for (entity, unit_id, _) in q_units.iter_mut() {
                     if unit_id.0 == id.0 {
@@ -89,6 +181,70 @@ pub fn handle_attack_result(
     }
 }
 
+#[derive(Component)]
+pub struct Cocoon;
+
+pub fn handle_morphing(
+    mut commands: Commands,
+    mut ev_action_result: EventReader<ActionResultEvent>,
+    mut q_units: Query<(Entity, &UnitId, &mut Sprite, Option<&Cocoon>)>,
+    scenario_state: Res<ScenarioState>,
+    unit_atlases: Res<UnitAtlases>,
+) {
+    const PLUM: Srgba = bevy::color::palettes::css::PLUM;
+    for _ in ev_action_result.read() {
+        for (entity, UnitId(unit_id), mut sprite, cocoon) in q_units.iter_mut() {
+            let Some(unit) = scenario_state.get_unit(*unit_id) else {
+                continue;
+            };
+
+            if unit.morph.is_some() {
+                sprite.color = PLUM.into();
+                if cocoon.is_none() {
+                    commands.entity(entity).insert(Cocoon);
+                }
+            } else if cocoon.is_some() {
+                info!("Unit {} finished morphing", unit_id);
+                commands.entity(entity).remove::<Cocoon>();
+                let unit_atlas = unit_atlases
+                    .atlas_map
+                    .get(&UnitType::from(unit.unit_type))
+                    .unwrap();
+                sprite.image = unit_atlas.texture.clone();
+                if let Some(atlas) = &mut sprite.texture_atlas {
+                    atlas.layout = unit_atlas.layout.clone();
+                    atlas.index = 0;
+                }
+            }
+        }
+    }
+}
+
+// Hides units while they're inside a transport, and shows them again where they are unloaded.
+pub fn handle_cargo(
+    mut commands: Commands,
+    mut ev_action_result: EventReader<ActionResultEvent>,
+    mut q_units: Query<(Entity, &UnitId, &mut Transform, &mut Visibility)>,
+    scenario_state: Res<ScenarioState>,
+) {
+    for _ in ev_action_result.read() {
+        for (entity, UnitId(unit_id), mut transform, mut visibility) in q_units.iter_mut() {
+            if let Some(unit) = scenario_state.get_unit(*unit_id) {
+                if *visibility == Visibility::Hidden {
+                    transform.translation.x = unit.position.x as f32 * TILE_SIZE;
+                    transform.translation.y = unit.position.y as f32 * TILE_SIZE;
+                    *visibility = Visibility::Inherited;
+                }
+            } else if scenario_state.is_carried(*unit_id) {
+                *visibility = Visibility::Hidden;
+            } else {
+                // Destroyed along with its transport.
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
 #[derive(Event)]
 pub struct DamageEvent {
     entity: Entity,
@@ -116,7 +272,7 @@ pub fn handle_damage(
             info!("Updating health indicator");
             match scenario_state.get_unit(unit_id.0) {
                 Some(unit) => {
-                    let max_health = unit.unit_type.value().max_health;
+                    let max_health = scenario_state.get_max_health(unit);
                     println!("max_health: {:?}", max_health);
                     let health_percent = new_hp / max_health;
                     let ceil_health = (health_percent * 10.0).ceil().max(0.0) as usize;
@@ -129,6 +285,9 @@ pub fn handle_damage(
                     } else if ceil_health < 10 {
                         *visibility = Visibility::Visible;
                         atlas.index = ceil_health - 1;
+                    } else {
+                        // Healed back to full health
+                        *visibility = Visibility::Hidden;
                     }
                 }
                 None => commands.entity(*entity).despawn_recursive(),